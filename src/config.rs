@@ -1,7 +1,14 @@
+use std::time::Duration;
+
 use clap::{Parser, ValueEnum};
+use glob::Pattern;
+use rustc_hash::FxHashMap;
+
+use crate::info::Module;
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Default, Parser, Eq, PartialEq)]
 #[command(author, version, about, long_about = None)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[arg(short, long)]
     pub scheme_name: Option<String>,
@@ -9,6 +16,54 @@ pub struct Config {
     pub orientation: Option<Orientation>,
     #[arg(short, long)]
     pub icon_name: Option<String>,
+    /// Comma-separated list of modules to show, in display order
+    #[arg(value_enum, short, long, value_delimiter = ',')]
+    pub modules: Option<Vec<Module>>,
+    /// Comma-separated list of modules to show in addition to `modules`
+    #[arg(value_enum, long, value_delimiter = ',')]
+    #[serde(default)]
+    pub show: Vec<Module>,
+    /// Comma-separated list of modules to hide
+    #[arg(value_enum, long, value_delimiter = ',')]
+    #[serde(default)]
+    pub hide: Vec<Module>,
+    /// Print every module except hidden ones in a machine-readable format instead of drawing the
+    /// logo
+    #[arg(value_enum, short, long)]
+    pub format: Option<OutputFormat>,
+    /// Where to draw the logo relative to the info column
+    #[arg(value_enum, short = 'p', long)]
+    pub logo_position: Option<LogoPosition>,
+    /// Unit to show CPU and GPU temperatures in
+    #[arg(value_enum, short, long)]
+    pub temperature_unit: Option<TemperatureUnit>,
+    /// How to spell out the uptime
+    #[arg(value_enum, short, long)]
+    pub uptime_style: Option<UptimeStyle>,
+    /// Which filesystems the disks module shows, set in a `[disks]` table
+    #[arg(skip)]
+    #[serde(default)]
+    pub disks: DiskConfig,
+    /// Which interfaces and addresses the ip module shows, set in a `[network]` table
+    #[arg(skip)]
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Show buffers, cache, shared memory and huge pages in the memory module
+    #[arg(long)]
+    #[serde(default)]
+    pub detailed_memory: bool,
+    /// Only draw the logo
+    #[arg(long, conflicts_with = "info_only")]
+    #[serde(skip)]
+    pub logo_only: bool,
+    /// Only print the info column
+    #[arg(long)]
+    #[serde(skip)]
+    pub info_only: bool,
+    /// Custom labels and formats, set per module in `[module.<name>]` tables
+    #[arg(skip)]
+    #[serde(default, rename = "module")]
+    pub formats: FxHashMap<Module, ModuleFormat>,
 }
 
 impl Config {
@@ -16,27 +71,78 @@ impl Config {
     #[must_use]
     pub fn with_icon(self, icon_name: impl Into<String>) -> Self {
         Self {
-            scheme_name: self.scheme_name,
             icon_name: Some(Into::<String>::into(icon_name)),
-            orientation: self.orientation,
+            ..self
         }
     }
     #[must_use]
     pub fn with_scheme_name(self, scheme_name: impl Into<String>) -> Self {
         Self {
             scheme_name: Some(Into::<String>::into(scheme_name)),
-            icon_name: self.icon_name,
-            orientation: self.orientation,
+            ..self
         }
     }
     #[must_use]
     pub fn with_orientation(self, orientation: &Orientation) -> Self {
         Self {
-            scheme_name: self.scheme_name,
-            icon_name: self.icon_name,
             orientation: Some(*orientation),
+            ..self
+        }
+    }
+    /// Builder method to set which modules are shown, and in what order
+    #[must_use]
+    pub fn with_modules(self, modules: impl Into<Vec<Module>>) -> Self {
+        Self {
+            modules: Some(modules.into()),
+            ..self
+        }
+    }
+    /// Builder method to print info in a machine-readable format
+    #[must_use]
+    pub fn with_format(self, format: OutputFormat) -> Self {
+        Self {
+            format: Some(format),
+            ..self
+        }
+    }
+    /// Builder method to set where the logo is drawn
+    #[must_use]
+    pub fn with_logo_position(self, logo_position: LogoPosition) -> Self {
+        Self {
+            logo_position: Some(logo_position),
+            ..self
+        }
+    }
+    /// Builder method to set the unit temperatures are shown in
+    #[must_use]
+    pub fn with_temperature_unit(self, temperature_unit: TemperatureUnit) -> Self {
+        Self {
+            temperature_unit: Some(temperature_unit),
+            ..self
         }
     }
+    /// Builder method to set how the uptime is spelled out
+    #[must_use]
+    pub fn with_uptime_style(self, uptime_style: UptimeStyle) -> Self {
+        Self {
+            uptime_style: Some(uptime_style),
+            ..self
+        }
+    }
+    /// Builder method to show a breakdown of memory usage
+    #[must_use]
+    pub fn with_detailed_memory(self) -> Self {
+        Self {
+            detailed_memory: true,
+            ..self
+        }
+    }
+    /// Builder method to customise how a module is displayed
+    #[must_use]
+    pub fn with_module_format(mut self, module: Module, format: ModuleFormat) -> Self {
+        self.formats.insert(module, format);
+        self
+    }
     /// Create new struct containing user settings
     #[must_use]
     pub fn new(
@@ -48,14 +154,95 @@ impl Config {
             scheme_name: scheme_name.map(|x| x.to_string()),
             orientation,
             icon_name: icon_name.map(|x| x.to_string()),
+            ..Self::default()
         }
     }
+    /// Merge two configs, preferring settings from `other`
     #[must_use]
     pub fn with_config(self, other: Self) -> Self {
+        // A module shown in `other` overrides it being hidden in `self`, and vice versa
+        let show = self
+            .show
+            .into_iter()
+            .filter(|module| !other.hide.contains(module))
+            .chain(other.show.iter().copied())
+            .collect();
+        let hide = self
+            .hide
+            .into_iter()
+            .filter(|module| !other.show.contains(module))
+            .chain(other.hide)
+            .collect();
         Self {
             scheme_name: other.scheme_name.or(self.scheme_name),
             orientation: other.orientation.or(self.orientation),
             icon_name: other.icon_name.or(self.icon_name),
+            modules: other.modules.or(self.modules),
+            show,
+            hide,
+            format: other.format.or(self.format),
+            logo_position: other.logo_position.or(self.logo_position),
+            temperature_unit: other.temperature_unit.or(self.temperature_unit),
+            uptime_style: other.uptime_style.or(self.uptime_style),
+            detailed_memory: other.detailed_memory || self.detailed_memory,
+            disks: if other.disks == DiskConfig::default() {
+                self.disks
+            } else {
+                other.disks
+            },
+            network: if other.network == NetworkConfig::default() {
+                self.network
+            } else {
+                other.network
+            },
+            logo_only: other.logo_only || self.logo_only,
+            info_only: other.info_only || self.info_only,
+            formats: self.formats.into_iter().chain(other.formats).collect(),
+        }
+    }
+    /// Builder method to show an extra module
+    #[must_use]
+    pub fn with_shown(mut self, module: Module) -> Self {
+        self.hide.retain(|hidden| *hidden != module);
+        self.show.push(module);
+        self
+    }
+    /// Builder method to hide a module
+    #[must_use]
+    pub fn with_hidden(mut self, module: Module) -> Self {
+        self.show.retain(|shown| *shown != module);
+        self.hide.push(module);
+        self
+    }
+    /// The modules to display, falling back to [`Module::DEFAULT`], with `show` appended and
+    /// `hide` removed
+    #[must_use]
+    pub fn modules(&self) -> Vec<Module> {
+        let mut modules = self.modules.as_deref().unwrap_or(Module::DEFAULT).to_vec();
+        for module in &self.show {
+            if !modules.contains(module) {
+                modules.push(*module);
+            }
+        }
+        modules.retain(|module| !self.hide.contains(module));
+        modules
+    }
+    /// The modules to serialize with `--format`: all of them, except those in `hide`
+    #[must_use]
+    pub fn format_modules(&self) -> Vec<Module> {
+        Module::value_variants()
+            .iter()
+            .filter(|module| !self.hide.contains(module))
+            .copied()
+            .collect()
+    }
+    /// Where to draw the logo, taking `--info-only` into account
+    #[must_use]
+    pub fn logo_position(&self) -> LogoPosition {
+        if self.info_only {
+            LogoPosition::None
+        } else {
+            self.logo_position.unwrap_or_default()
         }
     }
 }
@@ -65,3 +252,234 @@ pub enum Orientation {
     Horizontal,
     Vertical,
 }
+
+/// Devices that are never shown unless `include` matches them: snaps and other images, RAM disks
+/// and floppies
+const BUILTIN_EXCLUDE: [&str; 3] = ["/dev/loop*", "/dev/ram*", "/dev/fd*"];
+
+/// Filters for the disks module
+///
+/// `include` and `exclude` are globs matched against both the mount point and the device, e.g.
+/// `/boot/*` or `/dev/sd*`.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiskConfig {
+    /// If not empty, only show matching filesystems, even if they're removable or networked
+    pub include: Vec<String>,
+    /// Never show matching filesystems, in addition to [`BUILTIN_EXCLUDE`]
+    pub exclude: Vec<String>,
+    pub show_removable: bool,
+    /// Show NFS, SMB and other network filesystems, which can be slow to query
+    pub show_network: bool,
+}
+
+impl DiskConfig {
+    /// Whether to show the filesystem mounted at `mount` from `device`, only checking whether it's
+    /// removable if that matters
+    #[must_use]
+    pub fn shows(
+        &self,
+        mount: &str,
+        device: &str,
+        network: bool,
+        removable: impl FnOnce() -> bool,
+    ) -> bool {
+        let matches = |patterns: &[String]| matches_any(patterns, mount, device);
+        if matches(&self.exclude) {
+            return false;
+        }
+        if !self.include.is_empty() {
+            return matches(&self.include);
+        }
+        !matches_any(&BUILTIN_EXCLUDE, mount, device)
+            && (self.show_network || !network)
+            && (self.show_removable || !removable())
+    }
+}
+
+/// Whether any of the glob `patterns` matches the mount point or the device
+fn matches_any(patterns: &[impl AsRef<str>], mount: &str, device: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        Pattern::new(pattern.as_ref())
+            .is_ok_and(|pattern| pattern.matches(mount) || pattern.matches(device))
+    })
+}
+
+/// Which network interfaces and addresses to show
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub show_ipv6: bool,
+    /// Show bridges, container interfaces and other interfaces that aren't backed by hardware.
+    /// VPNs are always shown.
+    pub show_virtual: bool,
+}
+
+/// Overrides for how a module is displayed
+///
+/// Both are templates where `{name}` is replaced with the module's field of that name, e.g.
+/// `format = "{used} / {total} ({percent}%)"` for memory. Every module also has a `{value}` field
+/// containing its default formatting.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleFormat {
+    pub label: Option<String>,
+    pub format: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoPosition {
+    #[default]
+    Left,
+    Right,
+    Top,
+    None,
+}
+
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Format a temperature given in degrees Celsius, e.g. `54°C`
+    #[must_use]
+    pub fn format(self, celsius: f64) -> String {
+        match self {
+            Self::Celsius => format!("{celsius:.0}°C"),
+            Self::Fahrenheit => format!("{:.0}°F", celsius * 9.0 / 5.0 + 32.0),
+        }
+    }
+}
+
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum UptimeStyle {
+    /// e.g. `3 days, 4 hours, 12 mins`
+    #[default]
+    Long,
+    /// e.g. `3d 4h 12m`
+    Short,
+}
+
+impl UptimeStyle {
+    /// Format a duration to the minute, leaving out units that are zero, or in seconds if it's
+    /// under a minute
+    #[must_use]
+    pub fn format(self, duration: Duration) -> String {
+        let seconds = duration.as_secs();
+        let units = [
+            (seconds / 86400, "day", "d"),
+            (seconds / 3600 % 24, "hour", "h"),
+            (seconds / 60 % 60, "min", "m"),
+        ];
+        let units = if seconds < 60 {
+            vec![(seconds, "sec", "s")]
+        } else {
+            units.into_iter().filter(|(count, ..)| *count > 0).collect()
+        };
+        let units = units.into_iter().map(|(count, long, short)| match self {
+            Self::Long if count == 1 => format!("{count} {long}"),
+            Self::Long => format!("{count} {long}s"),
+            Self::Short => format!("{count}{short}"),
+        });
+        units.collect::<Vec<_>>().join(match self {
+            Self::Long => ", ",
+            Self::Short => " ",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_temperatures() {
+        assert_eq!(TemperatureUnit::Celsius.format(54.125), "54°C");
+        assert_eq!(TemperatureUnit::Celsius.format(-5.5), "-6°C");
+        assert_eq!(TemperatureUnit::Fahrenheit.format(54.125), "129°F");
+        assert_eq!(TemperatureUnit::Fahrenheit.format(-40.0), "-40°F");
+        assert_eq!(TemperatureUnit::Fahrenheit.format(100.0), "212°F");
+    }
+
+    #[test]
+    fn formats_uptimes() {
+        let uptime = Duration::from_secs(3 * 86400 + 4 * 3600 + 12 * 60 + 59);
+        assert_eq!(UptimeStyle::Long.format(uptime), "3 days, 4 hours, 12 mins");
+        assert_eq!(UptimeStyle::Short.format(uptime), "3d 4h 12m");
+
+        let uptime = Duration::from_secs(86400 + 60 + 5);
+        assert_eq!(UptimeStyle::Long.format(uptime), "1 day, 1 min");
+        assert_eq!(UptimeStyle::Short.format(uptime), "1d 1m");
+
+        let uptime = Duration::from_secs(3600 + 7);
+        assert_eq!(UptimeStyle::Long.format(uptime), "1 hour");
+    }
+
+    #[test]
+    fn formats_short_uptimes_in_seconds() {
+        assert_eq!(UptimeStyle::Long.format(Duration::from_secs(42)), "42 secs");
+        assert_eq!(UptimeStyle::Long.format(Duration::from_secs(1)), "1 sec");
+        assert_eq!(UptimeStyle::Short.format(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn always_excludes_builtin_devices_unless_included() {
+        let config = DiskConfig {
+            exclude: vec!["/boot*".into()],
+            ..DiskConfig::default()
+        };
+        assert!(!config.shows("/snap/core/1", "/dev/loop3", false, || false));
+        assert!(!config.shows("/boot", "/dev/sda1", false, || false));
+        assert!(config.shows("/", "/dev/sda2", false, || false));
+
+        let config = DiskConfig {
+            include: vec!["/dev/loop*".into()],
+            ..DiskConfig::default()
+        };
+        assert!(config.shows("/snap/core/1", "/dev/loop3", false, || false));
+        assert!(!config.shows("/", "/dev/sda2", false, || false));
+    }
+
+    #[test]
+    fn reads_module_tables() {
+        let config = toml::from_str::<Config>(
+            "[module.memory]\nlabel = \"RAM\"\nformat = \"{used} / {total}\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.formats[&Module::Memory],
+            ModuleFormat {
+                label: Some("RAM".into()),
+                format: Some("{used} / {total}".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[modules.memory]\nlabel = \"RAM\"\n").is_err());
+        assert!(toml::from_str::<Config>("[module.memory]\nlable = \"RAM\"\n").is_err());
+        assert!(toml::from_str::<Config>("[disks]\nshow_usb = true\n").is_err());
+        assert!(toml::from_str::<Config>("logo = \"arch\"\n").is_err());
+    }
+}
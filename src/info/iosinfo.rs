@@ -1,6 +1,11 @@
+use std::time::Duration;
+
 use arcstr::ArcStr;
 
-use crate::info::OSInfo;
+use crate::config::{DiskConfig, NetworkConfig};
+use crate::info::{
+    Battery, Cpu, Cursor, Disk, Font, Gpu, Interface, Memory, Monitor, OSInfo, Shell, Terminal, Wm,
+};
 
 pub struct IosInfo {}
 impl Default for IosInfo {
@@ -20,32 +25,32 @@ impl OSInfo for IosInfo {
         None
     }
 
-    fn cursor(&self) -> Option<ArcStr> {
+    fn cursor(&self) -> Option<Cursor> {
         None
     }
 
-    fn terminal(&self) -> Option<ArcStr> {
+    fn terminal(&self) -> Option<Terminal> {
         None
     }
 
-    fn term_font(&self) -> Option<ArcStr> {
+    fn term_font(&self) -> Option<Font> {
         None
     }
 
-    fn gpus(&self) -> Vec<arcstr::ArcStr> {
+    fn gpus(&self) -> Vec<Gpu> {
         Vec::new()
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self, _config: &DiskConfig) -> Vec<Disk> {
         Vec::new()
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -64,7 +69,7 @@ impl OSInfo for IosInfo {
         None
     }
 
-    fn displays(&self) -> Vec<arcstr::ArcStr> {
+    fn displays(&self) -> Vec<Monitor> {
         Vec::new()
     }
 
@@ -76,7 +81,7 @@ impl OSInfo for IosInfo {
         None
     }
 
-    fn wm(&self) -> Option<ArcStr> {
+    fn wm(&self) -> Option<Wm> {
         None
     }
 
@@ -84,11 +89,11 @@ impl OSInfo for IosInfo {
         None
     }
 
-    fn shell(&self) -> Option<ArcStr> {
+    fn shell(&self) -> Option<Shell> {
         None
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         None
     }
 
@@ -100,11 +105,11 @@ impl OSInfo for IosInfo {
         todo!()
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
+    fn uptime(&self) -> Option<Duration> {
         todo!()
     }
 
-    fn ip(&self) -> Vec<arcstr::ArcStr> {
+    fn ip(&self, _config: &NetworkConfig) -> Vec<Interface> {
         todo!()
     }
 
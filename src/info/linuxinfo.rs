@@ -1,35 +1,56 @@
 #![cfg(target_os = "linux")]
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
-use anyhow::anyhow;
-use arcstr::ArcStr;
-use glob::glob;
-use itertools::Itertools;
-use libc::{
-    getifaddrs, statvfs, timespec, AF_INET, AF_INET6, IFA_F_DEPRECATED, IFF_LOOPBACK, IFF_RUNNING,
+use crate::config::{DiskConfig, NetworkConfig};
+use crate::info::{
+    Battery, BootTime, Cpu, Cursor, Disk, Drive, Font, Gpu, Interface, Memory, Monitor, OSInfo,
+    PackageCount, Routing, Shell, Swap, Terminal, Wifi, Wm,
 };
-use pci_ids::Device;
+use arcstr::ArcStr;
+use libc::timespec;
 use platform_info::UNameAPI;
 use platform_info::{PlatformInfo, PlatformInfoAPI};
 use rayon::{
     prelude::{ParallelExtend, ParallelIterator},
     str::ParallelString,
 };
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 use std::env;
-use std::{
-    alloc::Layout,
-    ffi::{CStr, CString},
-    fs,
-    mem::{self, MaybeUninit},
-    net::{Ipv4Addr, Ipv6Addr},
-    sync::OnceLock,
-};
+use std::{ffi::CStr, fs, mem::MaybeUninit, sync::OnceLock, time::Duration};
+
+mod appearance;
+mod battery;
+mod cpu;
+mod dconf;
+mod disks;
+mod display;
+mod drm;
+mod edid;
+mod elf;
+#[cfg(test)]
+mod fake_fs;
+mod fonts;
+mod gpu;
+mod ini;
+mod memory;
+mod network;
+mod nl80211;
+mod packages;
+mod process;
+mod routing;
+mod sensors;
+mod shell;
+mod sqlite;
+mod storage;
+mod term_font;
+mod terminal;
+mod wifi;
+mod wm;
 
 pub struct LinuxInfo {
     uts: PlatformInfo,
     os_release: OnceLock<FxHashMap<ArcStr, ArcStr>>,
     machine_info: OnceLock<FxHashMap<ArcStr, ArcStr>>,
+    terminal: OnceLock<Option<Terminal>>,
+    appearance: OnceLock<appearance::Appearance>,
 }
 
 impl Default for LinuxInfo {
@@ -44,10 +65,12 @@ impl LinuxInfo {
             uts: PlatformInfo::new().unwrap(),
             os_release: OnceLock::default(),
             machine_info: OnceLock::default(),
+            terminal: OnceLock::default(),
+            appearance: OnceLock::default(),
         }
     }
 
-    fn parse_shellenv_like_file(&self, filename: &str) -> FxHashMap<ArcStr, ArcStr> {
+    fn parse_shellenv_like_file(filename: &str) -> FxHashMap<ArcStr, ArcStr> {
         let mut res = FxHashMap::default();
         if let Ok(data) = fs::read_to_string(filename) {
             res.par_extend(data.par_lines().map(|line| {
@@ -63,12 +86,20 @@ impl LinuxInfo {
 
     fn os_release(&self) -> &FxHashMap<ArcStr, ArcStr> {
         self.os_release
-            .get_or_init(|| self.parse_shellenv_like_file("/etc/os-release"))
+            .get_or_init(|| Self::parse_shellenv_like_file("/etc/os-release"))
     }
 
     fn machine_info(&self) -> &FxHashMap<ArcStr, ArcStr> {
         self.machine_info
-            .get_or_init(|| self.parse_shellenv_like_file("/etc/machine-info"))
+            .get_or_init(|| Self::parse_shellenv_like_file("/etc/machine-info"))
+    }
+
+    fn appearance(&self) -> &appearance::Appearance {
+        self.appearance.get_or_init(appearance::Appearance::load)
+    }
+
+    fn detect_terminal(&self) -> Option<&Terminal> {
+        self.terminal.get_or_init(terminal::terminal).as_ref()
     }
 }
 impl OSInfo for LinuxInfo {
@@ -119,34 +150,26 @@ impl OSInfo for LinuxInfo {
         Some(ArcStr::from(res.trim()))
     }
 
+    fn packages(&self) -> Vec<PackageCount> {
+        packages::packages()
+    }
+
     fn hostname(&self) -> Option<ArcStr> {
         Some(ArcStr::from(self.uts.nodename().to_str()?))
     }
 
-    fn displays(&self) -> Vec<ArcStr> {
-        || -> anyhow::Result<Vec<ArcStr>> {
-            let mut res = Vec::new();
-            let mut paths = glob("/sys/class/drm/card*-*/modes")?;
-            while let Some(Ok(path)) = paths.next() {
-                res.push(match fs::read_to_string(path)?.split_once('\n') {
-                    Some(x) => ArcStr::from(x.0),
-                    None => continue,
-                });
-            }
-            Ok(res)
-        }()
-        .ok()
-        .unwrap_or_default()
+    fn displays(&self) -> Vec<Monitor> {
+        display::displays()
     }
 
     fn machine(&self) -> Option<ArcStr> {
         self.machine_info()
             .get("HARDWARE_MODEL")
-            .map(|model| model.to_string())
+            .map(ArcStr::to_string)
             .or_else(|| {
                 self.machine_info()
                     .get("HARDWARE_VENDOR")
-                    .map(|vendor| vendor.to_string())
+                    .map(ArcStr::to_string)
             })
             .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_name").ok())
             .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_name").ok())
@@ -182,95 +205,31 @@ impl OSInfo for LinuxInfo {
         Some(ArcStr::from(self.uts.release().to_string_lossy()))
     }
 
-    #[allow(clippy::similar_names)]
-    fn gpus(&self) -> Vec<ArcStr> {
-        || -> anyhow::Result<Vec<ArcStr>> {
-            let mut res: Vec<ArcStr> = Vec::new();
-            let mut paths = glob("/sys/class/drm/card?/device")?;
-            while let Some(Ok(card)) = paths.next() {
-                let path = card.join("vendor");
-                if !path.exists() {
-                    continue;
-                }
-                let vid = u16::from_str_radix(&fs::read_to_string(path).unwrap().trim()[2..], 16)
-                    .unwrap();
-
-                let path = card.join("device");
-                if !path.exists() {
-                    continue;
-                }
-                let pid = u16::from_str_radix(&fs::read_to_string(path).unwrap().trim()[2..], 16)
-                    .unwrap();
-                let device = &Device::from_vid_pid(vid, pid).unwrap();
-                let vendor = device
-                    .vendor()
-                    .name()
-                    .replace("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD")
-                    .replace("Intel Corporation", "Intel");
-                res.push(arcstr::format!("{vendor} {}", device.name()));
-            }
-            Ok(res)
-        }()
-        .ok()
-        .unwrap_or_default()
+    fn gpus(&self) -> Vec<Gpu> {
+        gpu::gpus()
     }
 
-    // TODO
     fn theme(&self) -> Option<ArcStr> {
-        None
+        self.appearance().theme()
     }
 
-    // TODO
-    fn wm(&self) -> Option<ArcStr> {
-        None
+    fn wm(&self) -> Option<Wm> {
+        wm::wm()
     }
 
-    // TODO
     fn de(&self) -> Option<ArcStr> {
-        let de = env::var("XDG_CURRENT_DESKTOP").ok()?;
-        let protocol = env::var("XDG_SESSION_TYPE").ok();
-        Some(match protocol {
-            Some(proto) => arcstr::format!("{de} ({proto})"),
-            None => de.into(),
-        })
-    }
-
-    fn shell(&self) -> Option<ArcStr> {
-        let ppid = std::os::unix::process::parent_id();
-        fs::read_to_string(format!("/proc/{ppid}/comm"))
+        env::var("XDG_CURRENT_DESKTOP")
             .ok()
-            .map(|x| ArcStr::from(x.trim()))
+            .filter(|de| !de.is_empty())
+            .map(ArcStr::from)
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
-        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
-        let model = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("model name"))?
-            .split_once(':')?
-            .1
-            .trim();
-        let cores = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("siblings"))?
-            .split_once(':')?
-            .1
-            .trim();
+    fn shell(&self) -> Option<Shell> {
+        shell::shell()
+    }
 
-        let freq: f32 = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("cpu MHz"))?
-            .split_once(':')?
-            .1
-            .trim()
-            .parse()
-            .ok()?;
-        Some(arcstr::format!(
-            "{} ({}) @ {:.2} GHz)",
-            model,
-            cores,
-            freq / 1000.0
-        ))
+    fn cpu(&self) -> Option<Cpu> {
+        cpu::cpu()
     }
 
     fn username(&self) -> Option<ArcStr> {
@@ -284,181 +243,52 @@ impl OSInfo for LinuxInfo {
         }
     }
 
-    // TODO
     fn sys_font(&self) -> Option<ArcStr> {
-        None
+        self.appearance().font()
     }
 
-    // TODO
-    fn cursor(&self) -> Option<ArcStr> {
-        None
+    fn cursor(&self) -> Option<Cursor> {
+        self.appearance().cursor()
     }
 
-    // TODO
-    fn terminal(&self) -> Option<ArcStr> {
-        None
+    fn terminal(&self) -> Option<Terminal> {
+        self.detect_terminal().cloned()
     }
 
-    // TODO
-    fn term_font(&self) -> Option<ArcStr> {
-        None
+    fn term_font(&self) -> Option<Font> {
+        term_font::font(self.detect_terminal()?.name.as_ref()?)
     }
 
-    fn memory(&self) -> Option<ArcStr> {
-        let re = regex::Regex::new(r"Mem(Total|Available):\W*(\d*)").unwrap();
-        let mem = fs::read_to_string("/proc/meminfo").ok()?;
-        let caps: (u64, u64) = re
-            .captures_iter(&mem)
-            .map(|x| str::parse::<u64>(x.get(2).unwrap().as_str()).unwrap())
-            .collect_tuple()?;
+    fn memory(&self) -> Option<Memory> {
+        memory::memory()
+    }
 
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format((caps.0 - caps.1) << 10, 2),
-            bytecount_format(caps.0 << 10, 2),
-        ))
+    fn swap(&self) -> Vec<Swap> {
+        memory::swap()
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
-        let mut ipv4_addrs = FxHashSet::<Ipv4Addr>::default();
-        let mut ipv6_addrs = FxHashSet::<Ipv6Addr>::default();
-        unsafe {
-            let mut addrs = mem::MaybeUninit::<*mut libc::ifaddrs>::uninit();
-            getifaddrs(addrs.as_mut_ptr());
-            while let Some(addr) = addrs.assume_init().as_ref() {
-                if addr.ifa_addr.is_null() {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_RUNNING as u32 == 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_LOOPBACK as u32 != 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFA_F_DEPRECATED != 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET {
-                    #[allow(clippy::cast_ptr_alignment)]
-                    let ipv4 = (*(addr.ifa_addr).cast::<libc::sockaddr_in>())
-                        .sin_addr
-                        .s_addr
-                        .swap_bytes();
-                    ipv4_addrs.insert(Ipv4Addr::from(ipv4));
-                }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET6 {
-                    #[allow(clippy::cast_ptr_alignment)]
-                    let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
-                        .sin6_addr
-                        .s6_addr;
-                    if !ipv6.starts_with(&[0xfe, 0x80]) {
-                        ipv6_addrs.insert(Ipv6Addr::from(ipv6));
-                    }
-                }
-                // if addr.ifa_next.is_null() {
-                //     break;
-                // }
-                addrs = MaybeUninit::new(addr.ifa_next);
-            }
-        };
+    fn ip(&self, config: &NetworkConfig) -> Vec<Interface> {
+        network::interfaces(config)
+    }
+
+    fn wifi(&self) -> Vec<Wifi> {
+        wifi::wifi()
+    }
+
+    fn routing(&self) -> Option<Routing> {
+        routing::routing()
+    }
+
+    fn disks(&self, config: &DiskConfig) -> Vec<Disk> {
+        disks::disks(config)
+    }
+
+    fn storage(&self) -> Vec<Drive> {
+        storage::storage()
+    }
 
-        vec![
-            ArcStr::from(
-                ipv4_addrs
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect_vec()
-                    .join(", "),
-            ),
-            /*ipv6_addrs.iter().fold(ArcStr::new(), |x, y| {
-                (if x.is_empty() { x } else { x + ", " }) + &y.to_string()
-            }),*/
-        ]
-    }
-
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
-        (|| -> Option<Vec<(ArcStr, ArcStr)>> {
-            let mnt = fs::read_to_string("/proc/mounts").ok()?;
-            let re = regex::Regex::new(r"(^/dev/(loop|ram|fd))|(/var/snap)").unwrap();
-            Some(
-                mnt.par_lines()
-                    .filter_map(|line| -> Option<std::str::SplitAsciiWhitespace<'_>> {
-                        if re.is_match(line) {
-                            return None;
-                        }
-
-                        if line.starts_with("/rpool/") || line.starts_with("drvfs") {
-                            return Some(line.split_ascii_whitespace());
-                        }
-                        if !line.starts_with("/dev/") {
-                            return None;
-                        }
-                        return Some(line.split_ascii_whitespace());
-                    })
-                    .filter_map(|mut x| -> Option<(ArcStr, ArcStr)> {
-                        let (Some(_name), Some(mount), Some(_filesystemm)) =
-                            (x.next(), x.next(), x.next())
-                        else {
-                            return None;
-                        };
-                        unsafe {
-                            let buf: *mut statvfs =
-                                std::alloc::alloc(Layout::new::<statvfs>()).cast();
-
-                            statvfs(CString::new(mount).ok().unwrap().as_ptr(), buf);
-                            let total = (*buf).f_blocks;
-                            let size_used = total.checked_sub((*buf).f_bavail)?;
-                            let block_size = (*buf).f_bsize;
-                            if size_used == 0 {
-                                return None;
-                            }
-                            // println!("Size Used: {size_used}, Block Size {block_size}");
-                            size_used.checked_mul(block_size).map(|bytes| {
-                                (
-                                    arcstr::format!("Disk ({mount})"),
-                                    arcstr::format!(
-                                        "{}/ {}",
-                                        bytecount_format(bytes, 0),
-                                        bytecount_format(total * block_size, 0)
-                                    ),
-                                )
-                            })
-                        }
-                    })
-                    .collect::<Vec<(ArcStr, ArcStr)>>(),
-            )
-        })()
-        .unwrap_or_default()
-    }
-
-    fn battery(&self) -> Option<ArcStr> {
-        //TODO refactor this into a vector
-        Some(ArcStr::from(
-            glob::glob("/sys/class/power_supply/BAT*/")
-                .ok()?
-                .filter_map(|x| {
-                    x.map_err(|op| anyhow!(op))
-                        .and_then(|path| {
-                            let mut bat = fs::read_to_string(path.join("capacity"))?
-                                .trim()
-                                .to_string()
-                                + "% ";
-                            if let Ok(status) = fs::read_to_string(path.join("status")) {
-                                if !status.contains("Unknown") {
-                                    bat += &status;
-                                }
-                            }
-                            Ok(bat.trim().to_string())
-                        })
-                        .ok()
-                })
-                .collect_vec()
-                .join(", "),
-        ))
+    fn battery(&self) -> Vec<Battery> {
+        battery::battery()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -470,23 +300,49 @@ impl OSInfo for LinuxInfo {
             .map(ArcStr::from)
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
-        unsafe {
-            let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
-            libc::clock_gettime(libc::CLOCK_BOOTTIME, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec.into())
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
-            ))
-        }
+    fn uptime(&self) -> Option<Duration> {
+        let mut time = MaybeUninit::<timespec>::uninit();
+        // SAFETY: `time` is only read if the call succeeds
+        let time = unsafe {
+            if libc::clock_gettime(libc::CLOCK_BOOTTIME, time.as_mut_ptr()) != 0 {
+                return None;
+            }
+            time.assume_init()
+        };
+        Some(Duration::from_secs(time.tv_sec.try_into().ok()?))
+    }
+
+    // `time_t` and `tm_gmtoff` are 32 bits on some architectures
+    #[allow(clippy::useless_conversion)]
+    fn boot_time(&self) -> Option<BootTime> {
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        let timestamp = stat
+            .lines()
+            .find_map(|line| line.strip_prefix("btime "))?
+            .trim()
+            .parse::<libc::time_t>()
+            .ok()?;
+        let mut local = MaybeUninit::<libc::tm>::uninit();
+        // SAFETY: `local` is only read if the call succeeds, and `tm_zone` points into static
+        // storage that lives as long as the timezone isn't changed
+        let (utc_offset, timezone) = unsafe {
+            if libc::localtime_r(std::ptr::addr_of!(timestamp), local.as_mut_ptr()).is_null() {
+                return None;
+            }
+            let local = local.assume_init();
+            let timezone = (!local.tm_zone.is_null())
+                .then(|| ArcStr::from(CStr::from_ptr(local.tm_zone).to_string_lossy()));
+            (local.tm_gmtoff, timezone)
+        };
+        Some(BootTime {
+            timestamp: timestamp.into(),
+            utc_offset: utc_offset.try_into().ok()?,
+            timezone,
+        })
     }
 
-    // TODO
     fn icons(&self) -> Option<ArcStr> {
-        None
+        self.appearance().icons()
     }
     fn id(&self) -> ArcStr {
         self.os_release();
@@ -0,0 +1,257 @@
+//! GTK and Qt appearance settings: the theme, icon theme, font and cursor
+//!
+//! Each toolkit keeps its own settings, so values are reported per toolkit, e.g.
+//! `Adwaita-dark [GTK3/4], Breeze [Qt]`
+
+use std::env;
+
+use arcstr::ArcStr;
+use directories::BaseDirs;
+use itertools::Itertools;
+
+use super::{dconf::Dconf, fonts, ini::Ini};
+use crate::info::{Cursor, Font};
+
+const GNOME_INTERFACE: &str = "/org/gnome/desktop/interface/";
+
+/// Where to find one setting in each toolkit's config
+struct Setting {
+    /// Key in the GTK `settings.ini` files and `.gtkrc-2.0`
+    gtk: &'static str,
+    /// Key under `/org/gnome/desktop/interface/` in dconf
+    dconf: &'static str,
+    /// Section and key in `kdeglobals`
+    kde: (&'static str, &'static str),
+    /// Section and key in `qt5ct.conf` or `qt6ct.conf`
+    qtct: (&'static str, &'static str),
+}
+
+const THEME: Setting = Setting {
+    gtk: "gtk-theme-name",
+    dconf: "gtk-theme",
+    kde: ("KDE", "widgetStyle"),
+    qtct: ("Appearance", "style"),
+};
+
+const ICONS: Setting = Setting {
+    gtk: "gtk-icon-theme-name",
+    dconf: "icon-theme",
+    kde: ("Icons", "Theme"),
+    qtct: ("Appearance", "icon_theme"),
+};
+
+const FONT: Setting = Setting {
+    gtk: "gtk-font-name",
+    dconf: "font-name",
+    kde: ("General", "font"),
+    qtct: ("Fonts", "general"),
+};
+
+/// The config files that appearance settings are read from, loaded once and shared between probes
+#[derive(Default)]
+pub struct Appearance {
+    gtk2: Ini,
+    gtk3: Ini,
+    gtk4: Ini,
+    dconf: Option<Dconf>,
+    kdeglobals: Option<Ini>,
+    kcminputrc: Option<Ini>,
+    qtct: Option<Ini>,
+    /// `index.theme` files naming the default cursor theme
+    default_icons: Vec<Ini>,
+}
+
+/// One setting's value in each toolkit
+struct Toolkits {
+    gtk2: Option<String>,
+    gtk3: Option<String>,
+    gtk4: Option<String>,
+    qt: Option<String>,
+}
+
+impl Appearance {
+    pub fn load() -> Self {
+        let Some(dirs) = BaseDirs::new() else {
+            return Self::default();
+        };
+        let (home, config) = (dirs.home_dir(), dirs.config_dir());
+        let gtk2 = env::var_os("GTK2_RC_FILES")
+            .and_then(|files| env::split_paths(&files).find_map(Ini::read))
+            .or_else(|| Ini::read(home.join(".gtkrc-2.0")))
+            .unwrap_or_default();
+        Self {
+            gtk2,
+            gtk3: Ini::read(config.join("gtk-3.0/settings.ini")).unwrap_or_default(),
+            gtk4: Ini::read(config.join("gtk-4.0/settings.ini")).unwrap_or_default(),
+            dconf: Dconf::user(),
+            kdeglobals: Ini::read(config.join("kdeglobals")),
+            kcminputrc: Ini::read(config.join("kcminputrc")),
+            qtct: Ini::read(config.join("qt6ct/qt6ct.conf"))
+                .or_else(|| Ini::read(config.join("qt5ct/qt5ct.conf"))),
+            default_icons: [
+                home.join(".icons/default/index.theme"),
+                dirs.data_dir().join("icons/default/index.theme"),
+            ]
+            .into_iter()
+            .filter_map(Ini::read)
+            .collect(),
+        }
+    }
+
+    fn dconf(&self, key: &str) -> Option<String> {
+        let value = self
+            .dconf
+            .as_ref()?
+            .get(&format!("{GNOME_INTERFACE}{key}"))?;
+        value.as_str().map(str::to_owned)
+    }
+
+    fn setting(&self, setting: &Setting) -> Toolkits {
+        let gtk = |ini: &Ini| ini.get("Settings", setting.gtk).map(str::to_owned);
+        // GNOME keeps GTK 3 and 4 settings in dconf, and only writes `settings.ini` for other
+        // desktops
+        let dconf = self.dconf(setting.dconf);
+        Toolkits {
+            gtk2: self.gtk2.get("", setting.gtk).map(str::to_owned),
+            gtk3: dconf.clone().or_else(|| gtk(&self.gtk3)),
+            gtk4: dconf.or_else(|| gtk(&self.gtk4)),
+            qt: self
+                .kdeglobals
+                .as_ref()
+                .and_then(|kde| kde.get(setting.kde.0, setting.kde.1))
+                .or_else(|| {
+                    let (section, key) = setting.qtct;
+                    self.qtct.as_ref()?.get(section, key)
+                })
+                .map(str::to_owned),
+        }
+    }
+
+    pub fn theme(&self) -> Option<ArcStr> {
+        self.setting(&THEME)
+            .describe(|_, theme| Some(theme.to_owned()))
+    }
+
+    pub fn icons(&self) -> Option<ArcStr> {
+        self.setting(&ICONS)
+            .describe(|_, icons| Some(icons.to_owned()))
+    }
+
+    pub fn font(&self) -> Option<ArcStr> {
+        self.setting(&FONT).describe(|toolkit, font| {
+            let font = if toolkit == "Qt" {
+                fonts::qt(font)?
+            } else {
+                fonts::pango(font)?
+            };
+            Some(describe_font(&font))
+        })
+    }
+
+    pub fn cursor(&self) -> Option<Cursor> {
+        let env_size = env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok());
+        if let Some(theme) = env::var("XCURSOR_THEME")
+            .ok()
+            .filter(|theme| !theme.is_empty())
+        {
+            return Some(Cursor {
+                theme: ArcStr::from(theme),
+                size: env_size,
+            });
+        }
+        let kde = self.kcminputrc.as_ref().and_then(|kcminputrc| {
+            let size = kcminputrc.get("Mouse", "cursorSize");
+            Some((kcminputrc.get("Mouse", "cursorTheme")?.to_owned(), size))
+        });
+        let gtk = [
+            (&self.gtk3, "Settings"),
+            (&self.gtk4, "Settings"),
+            (&self.gtk2, ""),
+        ]
+        .into_iter()
+        .find_map(|(ini, section)| {
+            let size = ini.get(section, "gtk-cursor-theme-size");
+            Some((ini.get(section, "gtk-cursor-theme-name")?.to_owned(), size))
+        });
+        let dconf = self.dconf("cursor-theme").map(|theme| {
+            let size = self
+                .dconf
+                .as_ref()
+                .and_then(|dconf| dconf.get(&format!("{GNOME_INTERFACE}cursor-size")))
+                .and_then(|size| size.as_u32());
+            Cursor {
+                theme: ArcStr::from(theme),
+                size,
+            }
+        });
+        let parsed = |(theme, size): (String, Option<&str>)| Cursor {
+            theme: ArcStr::from(theme),
+            size: size.and_then(|size| size.parse().ok()),
+        };
+        let cursor = kde
+            .map(parsed)
+            .or(dconf)
+            .or_else(|| gtk.map(parsed))
+            .or_else(|| {
+                let theme = self
+                    .default_icons
+                    .iter()
+                    .find_map(|index| index.get("Icon Theme", "Inherits"))?;
+                Some(Cursor {
+                    theme: ArcStr::from(theme),
+                    size: None,
+                })
+            })?;
+        Some(Cursor {
+            size: env_size.or(cursor.size),
+            ..cursor
+        })
+    }
+}
+
+impl Toolkits {
+    /// Describe the value for each toolkit, grouping toolkits that share a value, e.g.
+    /// `Adwaita [GTK2/3/4], Breeze [Qt]`
+    fn describe(self, format: impl Fn(&str, &str) -> Option<String>) -> Option<ArcStr> {
+        let values = [
+            ("GTK2", self.gtk2),
+            ("GTK3", self.gtk3),
+            ("GTK4", self.gtk4),
+            ("Qt", self.qt),
+        ]
+        .into_iter()
+        .filter_map(|(toolkit, value)| Some((toolkit, format(toolkit, value.as_deref()?)?)));
+        let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+        for (toolkit, value) in values {
+            match groups.iter_mut().find(|(existing, _)| *existing == value) {
+                Some((_, toolkits)) => toolkits.push(toolkit),
+                None => groups.push((value, vec![toolkit])),
+            }
+        }
+        let description = groups
+            .iter()
+            .map(|(value, toolkits)| {
+                let gtk = toolkits
+                    .iter()
+                    .filter_map(|toolkit| toolkit.strip_prefix("GTK"))
+                    .join("/");
+                let toolkits = (!gtk.is_empty())
+                    .then(|| format!("GTK{gtk}"))
+                    .into_iter()
+                    .chain(toolkits.contains(&"Qt").then(|| String::from("Qt")))
+                    .join(", ");
+                format!("{value} [{toolkits}]")
+            })
+            .join(", ");
+        (!description.is_empty()).then(|| ArcStr::from(description))
+    }
+}
+
+fn describe_font(font: &Font) -> String {
+    font.size.map_or_else(
+        || font.name.to_string(),
+        |size| format!("{} ({size}pt)", font.name),
+    )
+}
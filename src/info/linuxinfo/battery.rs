@@ -0,0 +1,270 @@
+//! Batteries and AC adapters, from `/sys/class/power_supply`
+//!
+//! Drivers report either energy, in µWh and µW, or charge, in µAh and µA, so every calculation is
+//! tried with both.
+
+use std::{fs, path::Path, time::Duration};
+
+use arcstr::ArcStr;
+use glob::glob;
+
+use crate::info::Battery;
+
+/// A power supply's sysfs directory, e.g. `/sys/class/power_supply/BAT0`
+struct Supply<'a>(&'a Path);
+
+impl Supply<'_> {
+    fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.0.join(file))
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Some drivers report negative currents while discharging
+    fn number(&self, file: &str) -> Option<f64> {
+        self.read(file)?
+            .parse::<f64>()
+            .ok()
+            .map(f64::abs)
+            .filter(|value| *value > 0.0)
+    }
+
+    /// The first of `energy_{suffix}` and `charge_{suffix}` that's reported
+    fn amount(&self, suffix: &str) -> Option<f64> {
+        self.number(&format!("energy_{suffix}"))
+            .or_else(|| self.number(&format!("charge_{suffix}")))
+    }
+
+    /// Whether it powers a peripheral, e.g. a mouse, rather than the computer
+    fn peripheral(&self) -> bool {
+        self.read("scope").as_deref() == Some("Device")
+    }
+}
+
+pub fn battery() -> Vec<Battery> {
+    battery_in(Path::new("/sys"))
+}
+
+/// The batteries, with sysfs mounted at `sysfs`
+fn battery_in(sysfs: &Path) -> Vec<Battery> {
+    let Ok(paths) = glob(&format!("{}/class/power_supply/*", sysfs.display())) else {
+        return Vec::new();
+    };
+    let paths = paths.flatten().collect::<Vec<_>>();
+    let supplies = paths.iter().map(|path| Supply(path)).collect::<Vec<_>>();
+    let ac_online = ac_online(&supplies);
+    supplies
+        .iter()
+        .filter(|supply| supply.read("type").as_deref() == Some("Battery"))
+        .filter_map(|supply| battery_info(supply, ac_online))
+        .collect()
+}
+
+/// Whether any mains or USB power supply of the computer is plugged in, if there are any
+fn ac_online(supplies: &[Supply]) -> Option<bool> {
+    let adapters = supplies
+        .iter()
+        .filter(|supply| {
+            matches!(supply.read("type").as_deref(), Some("Mains" | "USB")) && !supply.peripheral()
+        })
+        .filter_map(|supply| supply.read("online"))
+        .collect::<Vec<_>>();
+    (!adapters.is_empty()).then(|| adapters.iter().any(|online| online != "0"))
+}
+
+/// `ac_online` is only reported for batteries that power the computer
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn battery_info(supply: &Supply, ac_online: Option<bool>) -> Option<Battery> {
+    let peripheral = supply.peripheral();
+    let name = supply.0.file_name()?.to_string_lossy();
+    let now = supply.amount("now");
+    let full = supply.amount("full");
+    let percent = supply
+        .read("capacity")
+        .and_then(|capacity| capacity.parse::<u8>().ok())
+        .or_else(|| Some((now? / full? * 100.0).round().min(100.0) as u8));
+    let status = supply.read("status").filter(|status| status != "Unknown");
+
+    let health = supply
+        .amount("full_design")
+        .zip(full)
+        .map(|(design, full)| full / design * 100.0);
+
+    // In watts, from µW or from µA by µV
+    let current = supply.number("current_now");
+    let power = supply
+        .number("power_now")
+        .map(|power| power / 1e6)
+        .or_else(|| Some(current? * supply.number("voltage_now")? / 1e12));
+
+    // How fast the stored energy or charge changes, in its own units per hour
+    let rate = if supply.number("energy_now").is_some() {
+        power.map(|power| power * 1e6)
+    } else {
+        current
+    };
+    let (reported, hours) = match status.as_deref() {
+        Some("Discharging") => (
+            supply.number("time_to_empty_now"),
+            now.zip(rate).map(|(now, rate)| now / rate),
+        ),
+        Some("Charging") => (
+            supply.number("time_to_full_now"),
+            now.zip(full)
+                .zip(rate)
+                .map(|((now, full), rate)| (full - now).max(0.0) / rate),
+        ),
+        _ => (None, None),
+    };
+    // Prefer the driver's own estimate, in seconds
+    let time_remaining = reported
+        .or_else(|| hours.map(|hours| hours * 3600.0))
+        .map(Duration::from_secs_f64)
+        .filter(|time| time.as_secs() > 0);
+
+    let model = [supply.read("manufacturer"), supply.read("model_name")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(Battery {
+        name: ArcStr::from(name),
+        model: Some(ArcStr::from(model)).filter(|model| !model.is_empty()),
+        percent,
+        status: status.map(ArcStr::from),
+        health,
+        power,
+        time_remaining,
+        ac_online: if peripheral { None } else { ac_online },
+        peripheral,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    #[test]
+    fn reads_energy_batteries() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/power_supply/AC/type", "Mains\n")
+            .write("class/power_supply/AC/online", "0\n")
+            .write("class/power_supply/BAT0/type", "Battery\n")
+            .write("class/power_supply/BAT0/status", "Discharging\n")
+            .write("class/power_supply/BAT0/capacity", "67\n")
+            .write("class/power_supply/BAT0/energy_now", "30000000\n")
+            .write("class/power_supply/BAT0/energy_full", "45000000\n")
+            .write("class/power_supply/BAT0/energy_full_design", "60000000\n")
+            .write("class/power_supply/BAT0/power_now", "16000000\n")
+            .write("class/power_supply/BAT0/manufacturer", "SMP\n")
+            .write("class/power_supply/BAT0/model_name", "5B10W13930\n");
+        assert_eq!(
+            battery_in(sysfs.root()),
+            [Battery {
+                name: ArcStr::from("BAT0"),
+                model: Some(ArcStr::from("SMP 5B10W13930")),
+                percent: Some(67),
+                status: Some(ArcStr::from("Discharging")),
+                health: Some(75.0),
+                power: Some(16.0),
+                time_remaining: Some(Duration::from_secs(6750)),
+                ac_online: Some(false),
+                peripheral: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn reads_charge_batteries() {
+        // Without `capacity`, and with a driver that reports a negative current
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/power_supply/ADP1/type", "Mains\n")
+            .write("class/power_supply/ADP1/online", "1\n")
+            .write("class/power_supply/BAT1/type", "Battery\n")
+            .write("class/power_supply/BAT1/status", "Charging\n")
+            .write("class/power_supply/BAT1/charge_now", "2200000\n")
+            .write("class/power_supply/BAT1/charge_full", "4000000\n")
+            .write("class/power_supply/BAT1/charge_full_design", "5000000\n")
+            .write("class/power_supply/BAT1/current_now", "-1600000\n")
+            .write("class/power_supply/BAT1/voltage_now", "12000000\n");
+        assert_eq!(
+            battery_in(sysfs.root()),
+            [Battery {
+                name: ArcStr::from("BAT1"),
+                model: None,
+                percent: Some(55),
+                status: Some(ArcStr::from("Charging")),
+                health: Some(80.0),
+                power: Some(19.2),
+                time_remaining: Some(Duration::from_secs(4050)),
+                ac_online: Some(true),
+                peripheral: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn peripherals_dont_count_as_ac_power() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/power_supply/AC/type", "Mains\n")
+            .write("class/power_supply/AC/online", "0\n")
+            .write("class/power_supply/hidpp_battery_0/type", "Battery\n")
+            .write("class/power_supply/hidpp_battery_0/scope", "Device\n")
+            .write("class/power_supply/hidpp_battery_0/status", "Discharging\n")
+            .write("class/power_supply/hidpp_battery_0/capacity", "40\n")
+            .write(
+                "class/power_supply/hidpp_battery_0/time_to_empty_now",
+                "93784\n",
+            )
+            .write(
+                "class/power_supply/hidpp_battery_0/model_name",
+                "MX Master 3\n",
+            )
+            .write("class/power_supply/hidpp_charger/type", "USB\n")
+            .write("class/power_supply/hidpp_charger/scope", "Device\n")
+            .write("class/power_supply/hidpp_charger/online", "1\n");
+        let batteries = battery_in(sysfs.root());
+        assert_eq!(
+            batteries,
+            [Battery {
+                name: ArcStr::from("hidpp_battery_0"),
+                model: Some(ArcStr::from("MX Master 3")),
+                percent: Some(40),
+                status: Some(ArcStr::from("Discharging")),
+                health: None,
+                power: None,
+                time_remaining: Some(Duration::from_secs(93784)),
+                ac_online: None,
+                peripheral: true,
+            }]
+        );
+        let supplies = [
+            sysfs.join("class/power_supply/AC"),
+            sysfs.join("class/power_supply/hidpp_charger"),
+        ];
+        let supplies = supplies.iter().map(|path| Supply(path)).collect::<Vec<_>>();
+        assert_eq!(ac_online(&supplies), Some(false));
+    }
+
+    #[test]
+    fn desktops_have_no_batteries() {
+        let sysfs = FakeFs::new();
+        assert_eq!(battery_in(sysfs.root()), []);
+        sysfs
+            .write(
+                "class/power_supply/ucsi-source-psy-USBC000:001/type",
+                "USB\n",
+            )
+            .write(
+                "class/power_supply/ucsi-source-psy-USBC000:001/online",
+                "0\n",
+            );
+        assert_eq!(battery_in(sysfs.root()), []);
+    }
+}
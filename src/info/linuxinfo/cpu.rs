@@ -0,0 +1,458 @@
+//! CPU detection from `/proc/cpuinfo` and `/sys/devices/system/cpu`, including ARM and RISC-V
+//! systems that don't report a model name
+
+use std::{fs, path::Path};
+
+use arcstr::ArcStr;
+use glob::glob;
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::sensors;
+use crate::info::Cpu;
+
+/// ARM `CPU implementer` IDs
+const IMPLEMENTERS: &[(u32, &str)] = &[
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x46, "Fujitsu"),
+    (0x48, "HiSilicon"),
+    (0x4e, "NVIDIA"),
+    (0x50, "APM"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x56, "Marvell"),
+    (0x61, "Apple"),
+    (0x69, "Intel"),
+    (0xc0, "Ampere"),
+];
+
+/// ARM `(CPU implementer, CPU part)` IDs
+const PARTS: &[(u32, u32, &str)] = &[
+    (0x41, 0xc05, "Cortex-A5"),
+    (0x41, 0xc07, "Cortex-A7"),
+    (0x41, 0xc08, "Cortex-A8"),
+    (0x41, 0xc09, "Cortex-A9"),
+    (0x41, 0xc0d, "Cortex-A17"),
+    (0x41, 0xc0f, "Cortex-A15"),
+    (0x41, 0xd01, "Cortex-A32"),
+    (0x41, 0xd02, "Cortex-A34"),
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd06, "Cortex-A65"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse-N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd0e, "Cortex-A76AE"),
+    (0x41, 0xd40, "Neoverse-V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd43, "Cortex-A65AE"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse-N2"),
+    (0x41, 0xd4a, "Neoverse-E1"),
+    (0x41, 0xd4b, "Cortex-A78C"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd4f, "Neoverse-V2"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0x46, 0x001, "A64FX"),
+    (0x48, 0xd01, "TaiShan v110"),
+    (0x4e, 0x004, "Carmel"),
+    (0x51, 0x800, "Kryo 2XX Gold"),
+    (0x51, 0x801, "Kryo 2XX Silver"),
+    (0x51, 0x802, "Kryo 3XX Gold"),
+    (0x51, 0x803, "Kryo 3XX Silver"),
+    (0x51, 0x804, "Kryo 4XX Gold"),
+    (0x51, 0x805, "Kryo 4XX Silver"),
+    (0x51, 0xc00, "Falkor"),
+    (0x51, 0xc01, "Saphira"),
+    (0x61, 0x022, "Icestorm"),
+    (0x61, 0x023, "Firestorm"),
+    (0x61, 0x032, "Blizzard"),
+    (0x61, 0x033, "Avalanche"),
+    (0xc0, 0xac3, "Ampere-1"),
+];
+
+/// Devicetree vendor prefixes of `SoC` makers
+const SOC_VENDORS: &[(&str, &str)] = &[
+    ("allwinner", "Allwinner"),
+    ("amlogic", "Amlogic"),
+    ("apple", "Apple"),
+    ("brcm", "Broadcom"),
+    ("fsl", "NXP"),
+    ("mediatek", "MediaTek"),
+    ("nvidia", "NVIDIA"),
+    ("nxp", "NXP"),
+    ("qcom", "Qualcomm"),
+    ("rockchip", "Rockchip"),
+    ("samsung", "Samsung"),
+    ("sifive", "SiFive"),
+    ("starfive", "StarFive"),
+    ("thead", "T-Head"),
+    ("ti", "TI"),
+];
+
+type Block<'a> = FxHashMap<&'a str, &'a str>;
+
+pub fn cpu() -> Option<Cpu> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpu_in(&cpuinfo, Path::new("/sys"))
+}
+
+/// The CPU described by the contents of `/proc/cpuinfo`, with sysfs mounted at `sysfs`
+fn cpu_in(cpuinfo: &str, sysfs: &Path) -> Option<Cpu> {
+    let blocks = blocks(cpuinfo);
+    let (sockets, cores, threads) = topology(sysfs).or_else(|| cpuinfo_topology(&blocks))?;
+    let model = model(&blocks, sysfs)?;
+    let max_mhz = max_freq_khz(sysfs)
+        .map(|khz| khz as f64 / 1000.0)
+        .or_else(|| {
+            blocks
+                .iter()
+                .filter_map(|block| block.get("cpu MHz")?.parse::<f64>().ok())
+                .max_by(f64::total_cmp)
+        });
+    Some(Cpu {
+        // Vendors pad the model name to a fixed width with spaces
+        model: ArcStr::from(model.split_whitespace().join(" ")),
+        sockets: Some(sockets),
+        cores: Some(cores),
+        threads: Some(threads),
+        freq_ghz: max_mhz.map(|mhz| mhz / 1000.0),
+        temperature: sensors::cpu_in(sysfs),
+    })
+}
+
+/// Each processor gets a block of `key : value` lines, and some architectures add a block of
+/// system-wide information at the end
+fn blocks(cpuinfo: &str) -> Vec<Block<'_>> {
+    cpuinfo
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .collect::<Block>()
+        })
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+/// The model name, or failing that the `SoC`, the ARM cores or whatever else identifies it
+fn model(blocks: &[Block], sysfs: &Path) -> Option<String> {
+    let field = |key: &str| blocks.iter().find_map(|block| block.get(key).copied());
+    field("model name")
+        .map(str::to_owned)
+        .or_else(|| soc(sysfs))
+        .or_else(|| arm_cores(blocks))
+        .or_else(|| field("uarch").map(str::to_owned))
+        .or_else(|| {
+            field("Processor")
+                .or_else(|| field("cpu model"))
+                .map(str::to_owned)
+        })
+        .or_else(|| field("isa").map(str::to_owned))
+}
+
+/// Count sockets, physical cores and logical processors from sysfs
+fn topology(sysfs: &Path) -> Option<(u32, u32, u32)> {
+    let mut sockets = FxHashSet::default();
+    let mut cores = FxHashSet::default();
+    let mut threads = 0;
+    for cpu in glob(&format!(
+        "{}/devices/system/cpu/cpu[0-9]*/topology",
+        sysfs.display()
+    ))
+    .ok()?
+    .flatten()
+    {
+        let read = |name: &str| -> Option<i64> {
+            fs::read_to_string(cpu.join(name)).ok()?.trim().parse().ok()
+        };
+        let package = read("physical_package_id")?;
+        sockets.insert(package);
+        // Core IDs are only unique within a die
+        cores.insert((
+            package,
+            read("die_id").unwrap_or_default(),
+            read("core_id")?,
+        ));
+        threads += 1;
+    }
+    counts(sockets.len(), cores.len(), threads)
+}
+
+/// Count sockets, physical cores and logical processors from `/proc/cpuinfo`, for older kernels
+fn cpuinfo_topology(blocks: &[Block]) -> Option<(u32, u32, u32)> {
+    let processors = blocks
+        .iter()
+        .filter(|block| block.contains_key("processor"))
+        .collect_vec();
+    let sockets = processors
+        .iter()
+        .map(|block| block.get("physical id"))
+        .collect::<FxHashSet<_>>();
+    // ARM and RISC-V don't report core IDs, and don't have SMT, so every processor is a core
+    let cores = processors
+        .iter()
+        .map(|block| {
+            (
+                block.get("physical id"),
+                block.get("core id").or_else(|| block.get("processor")),
+            )
+        })
+        .collect::<FxHashSet<_>>();
+    counts(sockets.len(), cores.len(), processors.len())
+}
+
+fn counts(sockets: usize, cores: usize, threads: usize) -> Option<(u32, u32, u32)> {
+    if threads == 0 {
+        return None;
+    }
+    Some((
+        u32::try_from(sockets).ok()?,
+        u32::try_from(cores).ok()?,
+        u32::try_from(threads).ok()?,
+    ))
+}
+
+/// The highest maximum clock of any core, in kHz
+fn max_freq_khz(sysfs: &Path) -> Option<u64> {
+    glob(&format!(
+        "{}/devices/system/cpu/cpu[0-9]*/cpufreq",
+        sysfs.display()
+    ))
+    .ok()?
+    .flatten()
+    .filter_map(|cpufreq| {
+        ["cpuinfo_max_freq", "scaling_max_freq"]
+            .iter()
+            .find_map(|file| {
+                fs::read_to_string(cpufreq.join(file))
+                    .ok()?
+                    .trim()
+                    .parse()
+                    .ok()
+            })
+    })
+    .max()
+}
+
+/// The `SoC` name from the devicetree, e.g. `brcm,bcm2711` becomes `Broadcom BCM2711`
+fn soc(sysfs: &Path) -> Option<String> {
+    let compatible = fs::read(sysfs.join("firmware/devicetree/base/compatible")).ok()?;
+    // Entries go from most to least specific, so the board comes first and the SoC last
+    let soc = compatible
+        .split(|byte| *byte == 0)
+        .rfind(|entry| !entry.is_empty())?;
+    let (vendor, chip) = std::str::from_utf8(soc).ok()?.split_once(',')?;
+    let (_, vendor) = SOC_VENDORS.iter().find(|(prefix, _)| *prefix == vendor)?;
+    Some(format!("{vendor} {}", chip.to_uppercase()))
+}
+
+/// Name the ARM cores from their implementer and part IDs, e.g. `Cortex-A76 + Cortex-A55`
+fn arm_cores(blocks: &[Block]) -> Option<String> {
+    let hex = |value: &str| u32::from_str_radix(value.trim_start_matches("0x"), 16).ok();
+    let cores = blocks
+        .iter()
+        .filter_map(|block| {
+            let implementer = hex(block.get("CPU implementer")?)?;
+            let part = hex(block.get("CPU part")?)?;
+            Some((implementer, part))
+        })
+        .unique()
+        .map(|(implementer, part)| {
+            PARTS
+                .iter()
+                .find(|(id, part_id, _)| *id == implementer && *part_id == part)
+                .map_or_else(
+                    || {
+                        let vendor = IMPLEMENTERS
+                            .iter()
+                            .find(|(id, _)| *id == implementer)
+                            .map_or("ARM", |(_, name)| name);
+                        format!("{vendor} 0x{part:03x}")
+                    },
+                    |(_, _, name)| (*name).to_owned(),
+                )
+        })
+        .join(" + ");
+    (!cores.is_empty()).then_some(cores)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    /// Two sockets of two cores with two threads each, numbered the way Linux does, with every
+    /// socket's first threads before their siblings
+    fn xeon() -> String {
+        let mut cpuinfo = String::new();
+        for processor in 0..8 {
+            write!(
+                cpuinfo,
+                "processor\t: {processor}\n\
+                 vendor_id\t: GenuineIntel\n\
+                 model name\t: Intel(R) Xeon(R) CPU E5-2680 v4 @ 2.40GHz   \n\
+                 cpu MHz\t\t: {}.000\n\
+                 physical id\t: {}\n\
+                 siblings\t: 4\n\
+                 core id\t\t: {}\n\
+                 cpu cores\t: 2\n\n",
+                1200 + processor * 100,
+                processor % 2,
+                processor / 2 % 2,
+            )
+            .unwrap();
+        }
+        cpuinfo
+    }
+
+    /// An RK3399, with four Cortex-A53s and two Cortex-A72s
+    fn rk3399() -> String {
+        let mut cpuinfo = String::new();
+        for processor in 0..6 {
+            let (variant, part, revision) = if processor < 4 {
+                (0, "d03", 4)
+            } else {
+                (1, "d08", 2)
+            };
+            write!(
+                cpuinfo,
+                "processor\t: {processor}\n\
+                 BogoMIPS\t: 48.00\n\
+                 Features\t: fp asimd evtstrm aes pmull sha1 sha2 crc32 cpuid\n\
+                 CPU implementer\t: 0x41\n\
+                 CPU architecture: 8\n\
+                 CPU variant\t: 0x{variant}\n\
+                 CPU part\t: 0x{part}\n\
+                 CPU revision\t: {revision}\n\n",
+            )
+            .unwrap();
+        }
+        cpuinfo
+    }
+
+    /// A `StarFive` JH7110, whose cores only report the ISA and microarchitecture
+    const JH7110: &str = "\
+processor\t: 0
+hart\t\t: 1
+isa\t\t: rv64imafdc_zicntr_zicsr_zifencei_zihpm_zba_zbb
+mmu\t\t: sv39
+uarch\t\t: sifive,u74-mc
+mvendorid\t: 0x489
+marchid\t\t: 0x8000000000000007
+mimpid\t\t: 0x4210427
+
+processor\t: 1
+hart\t\t: 2
+isa\t\t: rv64imafdc_zicntr_zicsr_zifencei_zihpm_zba_zbb
+mmu\t\t: sv39
+uarch\t\t: sifive,u74-mc
+mvendorid\t: 0x489
+marchid\t\t: 0x8000000000000007
+mimpid\t\t: 0x4210427
+";
+
+    #[test]
+    fn counts_sockets_from_cpuinfo() {
+        let cpuinfo = xeon();
+        assert_eq!(cpuinfo_topology(&blocks(&cpuinfo)), Some((2, 4, 8)));
+        let cpu = cpu_in(&cpuinfo, FakeFs::new().root()).unwrap();
+        assert_eq!(cpu.model, "Intel(R) Xeon(R) CPU E5-2680 v4 @ 2.40GHz");
+        assert_eq!(
+            (cpu.sockets, cpu.cores, cpu.threads),
+            (Some(2), Some(4), Some(8))
+        );
+        assert_eq!(cpu.freq_ghz, Some(1.9));
+    }
+
+    #[test]
+    fn counts_cores_from_sysfs() {
+        let sysfs = FakeFs::new();
+        for (cpu, package, core) in [(0, 0, 0), (1, 1, 0), (2, 0, 1), (3, 1, 1)] {
+            let topology = format!("devices/system/cpu/cpu{cpu}/topology");
+            sysfs
+                .write(
+                    &format!("{topology}/physical_package_id"),
+                    format!("{package}\n"),
+                )
+                .write(&format!("{topology}/die_id"), "0\n")
+                .write(&format!("{topology}/core_id"), format!("{core}\n"));
+        }
+        assert_eq!(topology(sysfs.root()), Some((2, 4, 4)));
+    }
+
+    #[test]
+    fn names_big_little_arm_cores() {
+        let cpuinfo = rk3399();
+        let blocks = blocks(&cpuinfo);
+        assert_eq!(cpuinfo_topology(&blocks), Some((1, 6, 6)));
+        assert_eq!(
+            arm_cores(&blocks).as_deref(),
+            Some("Cortex-A53 + Cortex-A72")
+        );
+
+        let sysfs = FakeFs::new();
+        assert_eq!(
+            model(&blocks, sysfs.root()).as_deref(),
+            Some("Cortex-A53 + Cortex-A72")
+        );
+        sysfs
+            .write(
+                "firmware/devicetree/base/compatible",
+                "pine64,rockpro64-v2.1\0pine64,rockpro64\0rockchip,rk3399\0",
+            )
+            .write(
+                "devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq",
+                "1416000\n",
+            )
+            .write(
+                "devices/system/cpu/cpu4/cpufreq/cpuinfo_max_freq",
+                "1800000\n",
+            );
+        let cpu = cpu_in(&cpuinfo, sysfs.root()).unwrap();
+        assert_eq!(cpu.model, "Rockchip RK3399");
+        assert_eq!(cpu.freq_ghz, Some(1.8));
+    }
+
+    #[test]
+    fn names_unknown_arm_parts_by_implementer() {
+        let blocks = blocks("processor\t: 0\nCPU implementer\t: 0x51\nCPU part\t: 0xfff\n");
+        assert_eq!(arm_cores(&blocks).as_deref(), Some("Qualcomm 0xfff"));
+    }
+
+    #[test]
+    fn names_risc_v_socs() {
+        let blocks = blocks(JH7110);
+        assert_eq!(cpuinfo_topology(&blocks), Some((1, 2, 2)));
+        let sysfs = FakeFs::new();
+        assert_eq!(
+            model(&blocks, sysfs.root()).as_deref(),
+            Some("sifive,u74-mc")
+        );
+        sysfs.write(
+            "firmware/devicetree/base/compatible",
+            "starfive,visionfive-2-v1.3b\0starfive,jh7110\0",
+        );
+        assert_eq!(
+            model(&blocks, sysfs.root()).as_deref(),
+            Some("StarFive JH7110")
+        );
+    }
+}
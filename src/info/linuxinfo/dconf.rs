@@ -0,0 +1,203 @@
+//! Just enough of the GVDB file format to look up keys in the user's dconf database, without
+//! going through `gsettings`
+//!
+//! See `gvdb/gvdb-format.h` in `GLib`
+
+use std::fs;
+
+use rustc_hash::FxHashMap;
+
+const SIGNATURE: &[u8] = b"GVariant";
+const HASH_ITEM_LEN: usize = 24;
+const NO_PARENT: u32 = u32::MAX;
+
+/// A value stored in dconf, for the types we care about
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Str(&'a str),
+    Bool(bool),
+    Int(i32),
+    UInt(u32),
+    Double(f64),
+    Other,
+}
+
+impl<'a> Value<'a> {
+    pub const fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Self::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Self::Int(value) => u32::try_from(*value).ok(),
+            Self::UInt(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+pub struct Dconf {
+    data: Vec<u8>,
+    /// Full key names, and where their serialized values are
+    keys: FxHashMap<String, (usize, usize)>,
+}
+
+impl Dconf {
+    /// Open the user's database, at `$XDG_CONFIG_HOME/dconf/user`
+    pub fn user() -> Option<Self> {
+        let dirs = directories::BaseDirs::new()?;
+        Self::parse(fs::read(dirs.config_dir().join("dconf/user")).ok()?)
+    }
+
+    fn parse(data: Vec<u8>) -> Option<Self> {
+        // Databases written on big-endian machines have a byte-swapped signature, and aren't
+        // supported
+        if data.get(..SIGNATURE.len())? != SIGNATURE {
+            return None;
+        }
+        let (root_start, root_end) = (u32_at(&data, 16)?, u32_at(&data, 20)?);
+        let bloom_words = u32_at(&data, root_start)? & ((1 << 27) - 1);
+        let buckets = u32_at(&data, root_start + 4)?;
+        let items_start = root_start + 8 + 4 * (bloom_words + buckets);
+        let items = (items_start..root_end.min(data.len()))
+            .step_by(HASH_ITEM_LEN)
+            .filter(|start| start + HASH_ITEM_LEN <= root_end)
+            .map(|start| Item::parse(&data, start))
+            .collect::<Option<Vec<_>>>()?;
+
+        let keys = items
+            .iter()
+            .filter(|item| item.kind == b'v')
+            .filter_map(|item| Some((item.full_key(&data, &items, 0)?, item.value)))
+            .collect();
+        Some(Self { data, keys })
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value<'_>> {
+        let (start, end) = *self.keys.get(key)?;
+        let variant = self.data.get(start..end)?;
+        // A variant is serialized as its value, a nul byte, then the value's type
+        let split = variant.iter().rposition(|byte| *byte == 0)?;
+        let (value, kind) = (&variant[..split], &variant[split + 1..]);
+        Some(match kind {
+            b"s" => Value::Str(std::str::from_utf8(value.strip_suffix(&[0])?).ok()?),
+            b"b" => Value::Bool(*value.first()? != 0),
+            b"i" => Value::Int(i32::from_le_bytes(value.try_into().ok()?)),
+            b"u" => Value::UInt(u32::from_le_bytes(value.try_into().ok()?)),
+            b"d" => Value::Double(f64::from_le_bytes(value.try_into().ok()?)),
+            _ => Value::Other,
+        })
+    }
+}
+
+struct Item {
+    parent: u32,
+    key: (usize, usize),
+    kind: u8,
+    value: (usize, usize),
+}
+
+impl Item {
+    fn parse(data: &[u8], start: usize) -> Option<Self> {
+        let key_start = u32_at(data, start + 8)?;
+        let key_len = u16::from_le_bytes(data.get(start + 12..start + 14)?.try_into().ok()?);
+        Some(Self {
+            parent: u32::from_le_bytes(data.get(start + 4..start + 8)?.try_into().ok()?),
+            key: (key_start, key_start + usize::from(key_len)),
+            kind: *data.get(start + 14)?,
+            value: (u32_at(data, start + 16)?, u32_at(data, start + 20)?),
+        })
+    }
+
+    /// Keys only store the part after their parent's key, e.g. `font` under `/org/gnome/`
+    fn full_key(&self, data: &[u8], items: &[Self], depth: usize) -> Option<String> {
+        let key = std::str::from_utf8(data.get(self.key.0..self.key.1)?).ok()?;
+        if self.parent == NO_PARENT {
+            return Some(key.to_owned());
+        }
+        // Guard against parents that loop back on themselves
+        if depth > items.len() {
+            return None;
+        }
+        let parent = items.get(self.parent as usize)?;
+        Some(parent.full_key(data, items, depth + 1)? + key)
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+    usize::try_from(u32::from_le_bytes(bytes)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// A database written by `dconf-service` after a few `gsettings set`s
+    fn fixture() -> Dconf {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dconf-user");
+        Dconf::parse(fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reads_values() {
+        let dconf = fixture();
+        let get = |key: &str| dconf.get(&format!("/org/gnome/desktop/{key}"));
+        assert_eq!(get("interface/gtk-theme"), Some(Value::Str("Adwaita-dark")));
+        assert_eq!(get("interface/font-name"), Some(Value::Str("Cantarell 11")));
+        assert_eq!(
+            get("wm/preferences/button-layout"),
+            Some(Value::Str("appmenu:minimize,close"))
+        );
+        assert_eq!(get("interface/cursor-size"), Some(Value::Int(32)));
+        assert_eq!(
+            get("interface/gtk-enable-primary-paste"),
+            Some(Value::Bool(false))
+        );
+        assert_eq!(get("interface/clock-show-seconds"), Some(Value::Bool(true)));
+        assert_eq!(
+            get("interface/text-scaling-factor"),
+            Some(Value::Double(1.25))
+        );
+        assert_eq!(get("peripherals/mouse/speed"), Some(Value::Double(0.5)));
+    }
+
+    #[test]
+    fn converts_values() {
+        let dconf = fixture();
+        let get = |key: &str| dconf.get(&format!("/org/gnome/desktop/interface/{key}"));
+        assert_eq!(get("cursor-size").and_then(|size| size.as_u32()), Some(32));
+        assert_eq!(get("cursor-size").and_then(|size| size.as_str()), None);
+        assert_eq!(
+            get("icon-theme").and_then(|theme| theme.as_str()),
+            Some("Papirus")
+        );
+        assert_eq!(get("icon-theme").and_then(|theme| theme.as_bool()), None);
+    }
+
+    #[test]
+    fn skips_missing_keys_and_directories() {
+        let dconf = fixture();
+        assert_eq!(dconf.get("/org/gnome/desktop/interface/color-scheme"), None);
+        assert_eq!(dconf.get("/org/gnome/desktop/interface/"), None);
+        assert_eq!(dconf.get("gtk-theme"), None);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(Dconf::parse(b"[org/gnome/desktop/interface]\n".to_vec()).is_none());
+        assert!(Dconf::parse(Vec::new()).is_none());
+    }
+}
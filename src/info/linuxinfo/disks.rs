@@ -0,0 +1,190 @@
+//! Mounted filesystems and their usage, from `/proc/self/mountinfo` and `statvfs(3)`
+
+use std::{ffi::CString, fs, mem::MaybeUninit};
+
+use arcstr::ArcStr;
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+
+use crate::{config::DiskConfig, info::Disk};
+
+/// Filesystems that are mounted over the network, and may hang if the server is unreachable
+const NETWORK: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "ncpfs",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.davfs2",
+    "davfs",
+];
+
+/// Filesystems that store data locally without being backed by a `/dev` node
+const DEVICELESS: &[&str] = &["zfs", "drvfs", "9p", "virtiofs"];
+
+/// One line of `/proc/self/mountinfo`
+struct Mount {
+    /// `major:minor`, which is the same for every mount of a filesystem, including bind mounts and
+    /// btrfs subvolumes
+    dev: String,
+    /// The directory within the filesystem that's mounted, which is `/` unless it's a bind mount
+    root: String,
+    /// Where it's mounted
+    target: String,
+    fs: String,
+    source: String,
+}
+
+pub fn disks(config: &DiskConfig) -> Vec<Disk> {
+    let Ok(mountinfo) = fs::read_to_string("/proc/self/mountinfo") else {
+        return Vec::new();
+    };
+    let candidates = mountinfo.lines().filter_map(parse).filter(|mount| {
+        let network = NETWORK.contains(&mount.fs.as_str());
+        let local = mount.source.starts_with("/dev/") || DEVICELESS.contains(&mount.fs.as_str());
+        // Skip `proc`, `tmpfs` and other virtual filesystems
+        (network || local)
+            && config.shows(&mount.target, &mount.source, network, || {
+                removable(&mount.dev)
+            })
+    });
+    let mut mounts = Vec::<Mount>::new();
+    let mut seen = FxHashMap::<String, usize>::default();
+    for mount in candidates {
+        match seen.get(&mount.dev) {
+            // Prefer the whole filesystem to a bind mount of part of it
+            Some(&idx) if mounts[idx].root != "/" && mount.root == "/" => mounts[idx] = mount,
+            Some(_) => {}
+            None => {
+                seen.insert(mount.dev.clone(), mounts.len());
+                mounts.push(mount);
+            }
+        }
+    }
+    mounts
+        .into_par_iter()
+        .filter_map(|mount| {
+            let (used, total) = usage(&mount.target)?;
+            (total > 0).then(|| Disk {
+                mount: ArcStr::from(mount.target),
+                fs: Some(ArcStr::from(mount.fs)),
+                used,
+                total,
+            })
+        })
+        .collect()
+}
+
+/// `id parent major:minor root mount options [optional fields...] - fs source super-options`
+fn parse(line: &str) -> Option<Mount> {
+    let (mount, filesystem) = line.split_once(" - ")?;
+    let mut mount = mount.split(' ');
+    let mut filesystem = filesystem.split(' ');
+    Some(Mount {
+        dev: mount.nth(2)?.to_owned(),
+        root: unescape(mount.next()?),
+        target: unescape(mount.next()?),
+        fs: filesystem.next()?.to_owned(),
+        source: unescape(filesystem.next()?),
+    })
+}
+
+/// Undo the octal escapes of spaces and other special characters, e.g. `\040`
+fn unescape(field: &str) -> String {
+    let mut res = Vec::with_capacity(field.len());
+    let mut bytes = field.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            res.push(byte);
+            continue;
+        }
+        let digits = bytes.clone().take(3).collect::<Vec<_>>();
+        match std::str::from_utf8(&digits)
+            .ok()
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            Some(escaped) if digits.len() == 3 => {
+                res.push(escaped);
+                bytes.nth(2);
+            }
+            _ => res.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+/// Whether the block device `major:minor` is removable media or plugged in over USB
+fn removable(dev: &str) -> bool {
+    let Ok(path) = fs::canonicalize(format!("/sys/dev/block/{dev}")) else {
+        return false;
+    };
+    if path.to_string_lossy().contains("/usb") {
+        return true;
+    }
+    // Partitions don't have a `removable` flag, but the disk they're on does
+    [path.join("removable"), path.join("../removable")]
+        .iter()
+        .any(|flag| fs::read_to_string(flag).is_ok_and(|flag| flag.trim() == "1"))
+}
+
+/// Used and total bytes of the filesystem mounted at `path`
+// The field types differ between architectures
+#[allow(clippy::useless_conversion)]
+fn usage(path: &str) -> Option<(u64, u64)> {
+    let path = CString::new(path).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is only read if the call succeeds
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+    let block_size = u64::from(stat.f_frsize);
+    let total = u64::from(stat.f_blocks) * block_size;
+    let used = u64::from(stat.f_blocks.checked_sub(stat.f_bfree)?) * block_size;
+    Some((used, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mountinfo() {
+        let mount = parse(
+            "36 35 98:0 /mnt1 /mnt/my\\040disk rw,noatime master:1 - ext4 /dev/sdb\\0401 rw,errors=continue",
+        )
+        .unwrap();
+        assert_eq!(mount.dev, "98:0");
+        assert_eq!(mount.root, "/mnt1");
+        assert_eq!(mount.target, "/mnt/my disk");
+        assert_eq!(mount.fs, "ext4");
+        assert_eq!(mount.source, "/dev/sdb 1");
+
+        // Without optional fields
+        let mount =
+            parse("29 1 259:2 / / rw,relatime - btrfs /dev/nvme0n1p2 rw,ssd,subvol=/@").unwrap();
+        assert_eq!((mount.root.as_str(), mount.target.as_str()), ("/", "/"));
+        assert_eq!(mount.fs, "btrfs");
+
+        assert!(parse("29 1 259:2 / / rw,relatime").is_none());
+    }
+
+    #[test]
+    fn unescapes_octal() {
+        assert_eq!(unescape(r"/media/USB\040Stick"), "/media/USB Stick");
+        assert_eq!(unescape(r"tab\011and\134backslash"), "tab\tand\\backslash");
+        assert_eq!(unescape(r"/caf\303\251"), "/café");
+        // Not a full escape, so left alone
+        assert_eq!(unescape(r"a\04"), r"a\04");
+        assert_eq!(unescape(r"a\09x"), r"a\09x");
+        assert_eq!(unescape("trailing\\"), "trailing\\");
+    }
+}
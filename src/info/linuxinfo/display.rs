@@ -0,0 +1,67 @@
+//! Connected monitors, from the DRM connectors in `/sys/class/drm`
+
+use std::{fs, path::Path};
+
+use arcstr::ArcStr;
+use glob::glob;
+use rustc_hash::FxHashMap;
+
+use super::{
+    drm::{self, Mode},
+    edid::Edid,
+};
+use crate::info::Monitor;
+
+pub fn displays() -> Vec<Monitor> {
+    let Ok(connectors) = glob("/sys/class/drm/card*-*") else {
+        return Vec::new();
+    };
+    // Current modes of each card's connectors, looked up as they're needed
+    let mut modes = FxHashMap::<String, FxHashMap<String, Mode>>::default();
+    connectors
+        .flatten()
+        .filter_map(|connector| {
+            let name = connector.file_name()?.to_str()?;
+            let (card, name) = name.split_once('-')?;
+            let read = |file: &str| fs::read_to_string(connector.join(file)).ok();
+            if read("status")?.trim() != "connected"
+                || read("enabled").is_some_and(|enabled| enabled.trim() == "disabled")
+            {
+                return None;
+            }
+            let edid = fs::read(connector.join("edid"))
+                .ok()
+                .and_then(|edid| Edid::parse(&edid));
+            let current = modes
+                .entry(card.to_owned())
+                .or_insert_with(|| drm::current_modes(&Path::new("/dev/dri").join(card)))
+                .remove(name);
+            let (width, height, refresh_hz) = if let Some(mode) = current {
+                (mode.width, mode.height, Some(mode.refresh_hz))
+            } else {
+                // Fall back to the preferred mode, which is listed first
+                let supported = read("modes")?;
+                let (width, height) = supported.lines().next()?.split_once('x')?;
+                let (width, height) = (
+                    width.parse().ok()?,
+                    height.trim_end_matches('i').parse().ok()?,
+                );
+                let refresh_hz = edid
+                    .as_ref()
+                    .and_then(|edid| edid.preferred)
+                    .filter(|preferred| (preferred.0, preferred.1) == (width, height))
+                    .map(|(_, _, refresh_hz)| refresh_hz);
+                (width, height, refresh_hz)
+            };
+            Some(Monitor {
+                name: edid.as_ref().map(|edid| ArcStr::from(edid.display_name())),
+                connector: Some(ArcStr::from(name)),
+                width,
+                height,
+                refresh_hz,
+                max_refresh_hz: edid.as_ref().and_then(|edid| edid.max_refresh_hz),
+                diagonal: edid.as_ref().and_then(Edid::diagonal),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,223 @@
+//! The modes that monitors are currently driven at, from the DRM kernel mode setting API
+//!
+//! sysfs only lists the modes a monitor supports, so this asks the driver which CRTC each
+//! connector is attached to and what mode that CRTC is scanning out. See `drm_mode.h`.
+
+use std::{fs::File, mem, os::fd::AsRawFd, path::Path};
+
+use rustc_hash::FxHashMap;
+
+/// Connector type names, indexed by `DRM_MODE_CONNECTOR_*`, matching the names in sysfs
+const CONNECTOR_TYPES: &[&str] = &[
+    "Unknown",
+    "VGA",
+    "DVI-I",
+    "DVI-D",
+    "DVI-A",
+    "Composite",
+    "SVIDEO",
+    "LVDS",
+    "Component",
+    "DIN",
+    "DP",
+    "HDMI-A",
+    "HDMI-B",
+    "TV",
+    "eDP",
+    "Virtual",
+    "DSI",
+    "DPI",
+    "Writeback",
+    "SPI",
+    "USB",
+];
+
+const FLAG_INTERLACE: u32 = 1 << 4;
+const FLAG_DBLSCAN: u32 = 1 << 5;
+
+#[repr(C)]
+#[derive(Default)]
+struct GetResources {
+    fb_id_ptr: u64,
+    crtc_id_ptr: u64,
+    connector_id_ptr: u64,
+    encoder_id_ptr: u64,
+    count_fbs: u32,
+    count_crtcs: u32,
+    count_connectors: u32,
+    count_encoders: u32,
+    min_width: u32,
+    max_width: u32,
+    min_height: u32,
+    max_height: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct ModeInfo {
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    kind: u32,
+    name: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct GetCrtc {
+    set_connectors_ptr: u64,
+    count_connectors: u32,
+    crtc_id: u32,
+    fb_id: u32,
+    x: u32,
+    y: u32,
+    gamma_size: u32,
+    mode_valid: u32,
+    mode: ModeInfo,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct GetEncoder {
+    encoder_id: u32,
+    encoder_type: u32,
+    crtc_id: u32,
+    possible_crtcs: u32,
+    possible_clones: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct GetConnector {
+    encoders_ptr: u64,
+    modes_ptr: u64,
+    props_ptr: u64,
+    prop_values_ptr: u64,
+    count_modes: u32,
+    count_props: u32,
+    count_encoders: u32,
+    encoder_id: u32,
+    connector_id: u32,
+    connector_type: u32,
+    connector_type_id: u32,
+    connection: u32,
+    mm_width: u32,
+    mm_height: u32,
+    subpixel: u32,
+    pad: u32,
+}
+
+/// `DRM_IOWR(nr, T)`
+#[allow(clippy::cast_possible_truncation)]
+const fn iowr<T>(nr: u8) -> libc::c_ulong {
+    (3 << 30) | ((mem::size_of::<T>() as libc::c_ulong) << 16) | (0x64 << 8) | nr as libc::c_ulong
+}
+
+const GET_RESOURCES: libc::c_ulong = iowr::<GetResources>(0xa0);
+const GET_CRTC: libc::c_ulong = iowr::<GetCrtc>(0xa1);
+const GET_ENCODER: libc::c_ulong = iowr::<GetEncoder>(0xa6);
+const GET_CONNECTOR: libc::c_ulong = iowr::<GetConnector>(0xa7);
+
+/// A mode a CRTC is scanning out
+pub struct Mode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_hz: f64,
+}
+
+/// The current mode of each active connector of `card`, e.g. `/dev/dri/card0`, by connector
+/// name, e.g. `DP-1`
+pub fn current_modes(card: &Path) -> FxHashMap<String, Mode> {
+    let Ok(file) = File::open(card) else {
+        return FxHashMap::default();
+    };
+    let ioctl = |request, arg: *mut libc::c_void| {
+        // SAFETY: each request is paired with the struct it expects, and any pointers in it are
+        // to buffers at least as long as their counts
+        unsafe { libc::ioctl(file.as_raw_fd(), request, arg) == 0 }
+    };
+
+    let mut res = GetResources::default();
+    if !ioctl(GET_RESOURCES, std::ptr::addr_of_mut!(res).cast()) {
+        return FxHashMap::default();
+    }
+    let mut connectors = vec![0u32; res.count_connectors as usize];
+    res = GetResources {
+        connector_id_ptr: connectors.as_mut_ptr() as u64,
+        count_connectors: res.count_connectors,
+        ..GetResources::default()
+    };
+    if !ioctl(GET_RESOURCES, std::ptr::addr_of_mut!(res).cast()) {
+        return FxHashMap::default();
+    }
+    connectors.truncate(res.count_connectors as usize);
+
+    connectors
+        .into_iter()
+        .filter_map(|connector_id| {
+            // Asking for no modes makes the driver probe the monitor again, which is slow, so
+            // make room for one
+            let mut mode = ModeInfo::default();
+            let mut connector = GetConnector {
+                connector_id,
+                modes_ptr: std::ptr::addr_of_mut!(mode) as u64,
+                count_modes: 1,
+                ..GetConnector::default()
+            };
+            if !ioctl(GET_CONNECTOR, std::ptr::addr_of_mut!(connector).cast())
+                || connector.encoder_id == 0
+            {
+                return None;
+            }
+            let mut encoder = GetEncoder {
+                encoder_id: connector.encoder_id,
+                ..GetEncoder::default()
+            };
+            if !ioctl(GET_ENCODER, std::ptr::addr_of_mut!(encoder).cast()) || encoder.crtc_id == 0 {
+                return None;
+            }
+            let mut crtc = GetCrtc {
+                crtc_id: encoder.crtc_id,
+                ..GetCrtc::default()
+            };
+            if !ioctl(GET_CRTC, std::ptr::addr_of_mut!(crtc).cast()) || crtc.mode_valid == 0 {
+                return None;
+            }
+            let kind = CONNECTOR_TYPES.get(connector.connector_type as usize)?;
+            let name = format!("{kind}-{}", connector.connector_type_id);
+            Some((name, Mode::from(&crtc.mode)))
+        })
+        .collect()
+}
+
+impl From<&ModeInfo> for Mode {
+    fn from(mode: &ModeInfo) -> Self {
+        let total = f64::from(mode.htotal) * f64::from(mode.vtotal);
+        let mut refresh_hz = if total > 0.0 {
+            f64::from(mode.clock) * 1000.0 / total
+        } else {
+            f64::from(mode.vrefresh)
+        };
+        if mode.flags & FLAG_INTERLACE != 0 {
+            refresh_hz *= 2.0;
+        }
+        if mode.flags & FLAG_DBLSCAN != 0 {
+            refresh_hz /= 2.0;
+        }
+        Self {
+            width: u32::from(mode.hdisplay),
+            height: u32::from(mode.vdisplay),
+            refresh_hz,
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Just enough of the EDID format to name a monitor and work out its size and refresh rates
+//!
+//! See VESA E-EDID Release A, Revision 2
+
+const HEADER: &[u8] = &[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Offsets of the four 18-byte descriptors in the base block
+const DESCRIPTORS: [usize; 4] = [0x36, 0x48, 0x5a, 0x6c];
+
+/// Descriptor tag of the monitor's name
+const NAME: u8 = 0xfc;
+/// Descriptor tag of the monitor's supported range of refresh rates
+const RANGE_LIMITS: u8 = 0xfd;
+
+/// Plug and Play IDs of common monitor and panel makers
+const MANUFACTURERS: &[(&str, &str)] = &[
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Innolux"),
+    ("DEL", "Dell"),
+    ("ENC", "EIZO"),
+    ("GBT", "Gigabyte"),
+    ("GSM", "LG"),
+    ("HPN", "HP"),
+    ("HWP", "HP"),
+    ("IVM", "iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+    ("XMI", "Xiaomi"),
+];
+
+pub struct Edid {
+    /// The manufacturer's three-letter Plug and Play ID, e.g. `DEL`
+    pub manufacturer: String,
+    pub name: Option<String>,
+    /// Width and height of the visible area, in millimetres
+    pub size_mm: Option<(u32, u32)>,
+    /// Resolution and refresh rate of the preferred mode
+    pub preferred: Option<(u32, u32, f64)>,
+    /// Highest supported refresh rate, in Hz
+    pub max_refresh_hz: Option<u32>,
+}
+
+impl Edid {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..HEADER.len())? != HEADER || data.len() < 128 {
+            return None;
+        }
+        // Three five-bit letters, where 1 is `A`
+        let id = u16::from_be_bytes([data[8], data[9]]);
+        let manufacturer = [10, 5, 0]
+            .iter()
+            .map(|shift| char::from(b'@' + ((id >> shift) & 0x1f) as u8))
+            .collect();
+
+        let mut edid = Self {
+            manufacturer,
+            name: None,
+            // The base block only has the size to the nearest centimetre
+            size_mm: Some((u32::from(data[21]) * 10, u32::from(data[22]) * 10))
+                .filter(|(width, height)| *width > 0 && *height > 0),
+            preferred: None,
+            max_refresh_hz: None,
+        };
+        for offset in DESCRIPTORS {
+            let descriptor = &data[offset..offset + 18];
+            if descriptor[..2] != [0, 0] {
+                edid.timing(descriptor);
+                continue;
+            }
+            match descriptor[3] {
+                NAME => {
+                    let name = descriptor[5..]
+                        .split(|byte| *byte == b'\n')
+                        .next()
+                        .unwrap_or_default();
+                    let name = String::from_utf8_lossy(name).trim().to_owned();
+                    edid.name = Some(name).filter(|name| !name.is_empty());
+                }
+                RANGE_LIMITS => {
+                    // Rates above 255 Hz are flagged as being offset by 255
+                    let offset = if descriptor[4] & 0b10 == 0 { 0 } else { 255 };
+                    edid.max_refresh_hz = Some(u32::from(descriptor[6]) + offset);
+                }
+                _ => {}
+            }
+        }
+        Some(edid)
+    }
+
+    /// Read a detailed timing descriptor, the first of which is the preferred mode
+    fn timing(&mut self, descriptor: &[u8]) {
+        let nibbles =
+            |low: u8, high: u8, shift: u8| u32::from(low) | (u32::from((high >> shift) & 0xf) << 8);
+        let clock_khz = u32::from(u16::from_le_bytes([descriptor[0], descriptor[1]])) * 10;
+        let width = nibbles(descriptor[2], descriptor[4], 4);
+        let height = nibbles(descriptor[5], descriptor[7], 4);
+        let total = (width + nibbles(descriptor[3], descriptor[4], 0))
+            * (height + nibbles(descriptor[6], descriptor[7], 0));
+        if self.preferred.is_none() && total > 0 {
+            let refresh = f64::from(clock_khz) * 1000.0 / f64::from(total);
+            self.preferred = Some((width, height, refresh));
+        }
+        let size = (
+            nibbles(descriptor[12], descriptor[14], 4),
+            nibbles(descriptor[13], descriptor[14], 0),
+        );
+        if size.0 > 0 && size.1 > 0 {
+            self.size_mm = Some(size);
+        }
+    }
+
+    /// The monitor's name, including its manufacturer if the name doesn't already, e.g.
+    /// `DELL U2720Q`
+    pub fn display_name(&self) -> String {
+        let manufacturer = MANUFACTURERS
+            .iter()
+            .find(|(id, _)| *id == self.manufacturer)
+            .map_or(self.manufacturer.as_str(), |(_, name)| name);
+        match &self.name {
+            Some(name)
+                if name
+                    .to_lowercase()
+                    .starts_with(&manufacturer.to_lowercase()) =>
+            {
+                name.clone()
+            }
+            Some(name) => format!("{manufacturer} {name}"),
+            None => manufacturer.to_owned(),
+        }
+    }
+
+    /// The diagonal of the visible area, in inches
+    pub fn diagonal(&self) -> Option<f64> {
+        let (width, height) = self.size_mm?;
+        Some(f64::from(width).hypot(f64::from(height)) / 25.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The base block of a Dell U2720Q
+    const U2720Q: [u8; 128] = [
+        0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x10, 0xac, 0xfb, 0xa0, 0x53, 0x35, 0x4b,
+        0x4c, 0x0e, 0x1e, 0x01, 0x04, 0xb5, 0x3c, 0x22, 0x78, 0x3a, 0x1d, 0xf5, 0xae, 0x4f, 0x35,
+        0xb3, 0x25, 0x0d, 0x50, 0x54, 0xa5, 0x4b, 0x00, 0x81, 0x00, 0xb3, 0x00, 0xd1, 0x00, 0x71,
+        0x4f, 0xa9, 0x40, 0x81, 0x80, 0xd1, 0xc0, 0x01, 0x01, 0x4d, 0xd0, 0x00, 0xa0, 0xf0, 0x70,
+        0x3e, 0x80, 0x30, 0x20, 0x35, 0x00, 0x55, 0x50, 0x21, 0x00, 0x00, 0x1e, 0x00, 0x00, 0x00,
+        0xfc, 0x00, 0x44, 0x45, 0x4c, 0x4c, 0x20, 0x55, 0x32, 0x37, 0x32, 0x30, 0x51, 0x0a, 0x20,
+        0x00, 0x00, 0x00, 0xfd, 0x00, 0x1d, 0x4c, 0x87, 0x87, 0x3c, 0x01, 0x0a, 0x20, 0x20, 0x20,
+        0x20, 0x20, 0x20, 0x00, 0x00, 0x00, 0xff, 0x00, 0x37, 0x44, 0x30, 0x4d, 0x4b, 0x38, 0x33,
+        0x0a, 0x20, 0x20, 0x20, 0x20, 0x20, 0x01, 0xac,
+    ];
+
+    #[test]
+    fn parses_base_block() {
+        let edid = Edid::parse(&U2720Q).unwrap();
+        assert_eq!(edid.manufacturer, "DEL");
+        assert_eq!(edid.name.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(edid.display_name(), "DELL U2720Q");
+        // The detailed timing's size, in millimetres, replaces the header's, in centimetres
+        assert_eq!(edid.size_mm, Some((597, 336)));
+        assert_eq!(edid.diagonal().map(f64::round), Some(27.0));
+        let (width, height, refresh) = edid.preferred.unwrap();
+        assert_eq!((width, height), (3840, 2160));
+        assert!((refresh - 59.997).abs() < 0.001);
+        assert_eq!(edid.max_refresh_hz, Some(76));
+    }
+
+    #[test]
+    fn names_by_manufacturer() {
+        let mut edid = Edid::parse(&U2720Q).unwrap();
+        edid.name = Some("U2720Q".to_owned());
+        assert_eq!(edid.display_name(), "Dell U2720Q");
+        edid.name = None;
+        assert_eq!(edid.display_name(), "Dell");
+        edid.manufacturer = "ZZZ".to_owned();
+        assert_eq!(edid.display_name(), "ZZZ");
+    }
+
+    #[test]
+    fn offsets_high_refresh_rates() {
+        let mut data = U2720Q;
+        // 360 Hz, flagged as 255 + 105
+        data[0x5a + 4] = 0b10;
+        data[0x5a + 6] = 105;
+        assert_eq!(Edid::parse(&data).unwrap().max_refresh_hz, Some(360));
+    }
+
+    #[test]
+    fn rejects_bad_blocks() {
+        assert!(Edid::parse(&U2720Q[..127]).is_none());
+        assert!(Edid::parse(&[0u8; 128]).is_none());
+        assert!(Edid::parse(&[]).is_none());
+    }
+}
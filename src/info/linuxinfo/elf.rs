@@ -0,0 +1,119 @@
+//! Just enough of the ELF format to pull a section's contents out of an executable
+//!
+//! See `elf(5)`
+
+use std::{fs, path::Path};
+
+const MAGIC: &[u8] = b"\x7fELF";
+
+/// Where the fields we need are, which differs between 32 and 64-bit files
+struct Layout {
+    /// Width of addresses and offsets
+    word: usize,
+    /// Offset of `e_shoff`, the section header table's position, in the file header
+    table: usize,
+    /// Offset of `e_shentsize`, followed by `e_shnum` and `e_shstrndx`
+    table_info: usize,
+    /// Offset of `sh_offset`, followed by `sh_size`, in a section header
+    section: usize,
+}
+
+const ELF32: Layout = Layout {
+    word: 4,
+    table: 0x20,
+    table_info: 0x2e,
+    section: 0x10,
+};
+
+const ELF64: Layout = Layout {
+    word: 8,
+    table: 0x28,
+    table_info: 0x3a,
+    section: 0x18,
+};
+
+/// The contents of the section called `name`, e.g. `.rodata`
+pub fn section(path: &Path, name: &str) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    if data.get(..MAGIC.len())? != MAGIC {
+        return None;
+    }
+    // Only little-endian executables are supported
+    if *data.get(5)? != 1 {
+        return None;
+    }
+    let layout = match data.get(4)? {
+        1 => &ELF32,
+        2 => &ELF64,
+        _ => return None,
+    };
+    let table = read(&data, layout.table, layout.word)?;
+    let entry_size = read(&data, layout.table_info, 2)?;
+    let count = read(&data, layout.table_info + 2, 2)?;
+    let names_idx = read(&data, layout.table_info + 4, 2)?;
+    let header = |idx: usize| table.checked_add(idx.checked_mul(entry_size)?);
+    let location = |header: usize| {
+        let offset = read(&data, header + layout.section, layout.word)?;
+        let size = read(&data, header + layout.section + layout.word, layout.word)?;
+        Some((offset, offset.checked_add(size)?))
+    };
+
+    // Section names are stored in a section of their own
+    let (names, _) = location(header(names_idx)?)?;
+    (0..count).find_map(|idx| {
+        let header = header(idx)?;
+        let section_name = names.checked_add(read(&data, header, 4)?)?;
+        let section_name = data.get(section_name..)?.split(|byte| *byte == 0).next()?;
+        if section_name != name.as_bytes() {
+            return None;
+        }
+        let (start, end) = location(header)?;
+        data.get(start..end).map(<[u8]>::to_vec)
+    })
+}
+
+/// Read a little-endian integer `width` bytes wide
+fn read(data: &[u8], offset: usize, width: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset.checked_add(width)?)?;
+    let value = bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+    usize::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    const RODATA: &[u8] = b"@(#)Bash version 5.2.15(1) release GNU\0";
+
+    /// Object files compiled from a single constant string, as bash embeds its version
+    fn fixture(bits: u8) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/rodata{bits}.o"))
+    }
+
+    #[test]
+    fn reads_sections() {
+        for bits in [32, 64] {
+            assert_eq!(section(&fixture(bits), ".rodata").as_deref(), Some(RODATA));
+            assert_eq!(section(&fixture(bits), ".missing"), None);
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let dir = FakeFs::new();
+        let mut data = fs::read(fixture(64)).unwrap();
+        // Big-endian
+        data[5] = 2;
+        dir.write("big-endian.o", data)
+            .write("script.sh", "#!/bin/sh\n");
+        assert_eq!(section(&dir.join("big-endian.o"), ".rodata"), None);
+        assert_eq!(section(&dir.join("script.sh"), ".rodata"), None);
+        assert_eq!(section(&dir.join("missing"), ".rodata"), None);
+    }
+}
@@ -0,0 +1,52 @@
+//! Temporary directory trees that stand in for sysfs and procfs in tests
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory that's removed when it's dropped
+pub struct FakeFs(PathBuf);
+
+impl FakeFs {
+    pub fn new() -> Self {
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let root = env::temp_dir().join(format!("mirafetch-test-{}-{count}", process::id()));
+        fs::create_dir_all(&root).unwrap();
+        Self(root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: &str) -> PathBuf {
+        self.0.join(path)
+    }
+
+    /// Write `contents` to `path` under the root, creating any missing directories
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> &Self {
+        let path = self.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        self
+    }
+
+    /// Link `link` to `target`, both under the root
+    pub fn symlink(&self, target: &str, link: &str) -> &Self {
+        let link = self.join(link);
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(self.join(target), link).unwrap();
+        self
+    }
+}
+
+impl Drop for FakeFs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,108 @@
+//! Parsers for the ways toolkits and terminals write font names and sizes
+
+use arcstr::ArcStr;
+
+use crate::info::Font;
+
+/// A font, unless `name` is blank
+pub fn named(name: &str, size: Option<f64>) -> Option<Font> {
+    let name = name.trim();
+    (!name.is_empty()).then(|| Font {
+        name: ArcStr::from(name),
+        size,
+    })
+}
+
+/// Parse a Pango font description, like `Cantarell Bold 11`
+pub fn pango(description: &str) -> Option<Font> {
+    match description.rsplit_once(' ') {
+        Some((name, size)) if size.parse::<f64>().is_ok() => named(name, size.parse().ok()),
+        _ => named(description, None),
+    }
+}
+
+/// Parse the first font in a fontconfig pattern, like `Hack:size=11:style=Bold, Noto Color Emoji`
+pub fn fontconfig(pattern: &str) -> Option<Font> {
+    let mut parts = pattern.split(',').next()?.split(':');
+    let name = parts.next()?;
+    let size = parts
+        .filter_map(|part| part.split_once('='))
+        .find(|(key, _)| *key == "size")
+        .and_then(|(_, size)| size.trim().parse().ok());
+    named(name, size)
+}
+
+/// Parse a Qt font description, like `Hack,10,-1,5,50,0,0,0,0,0`
+pub fn qt(description: &str) -> Option<Font> {
+    let mut parts = description.split(',');
+    let name = parts.next()?;
+    named(name, parts.next().and_then(|size| size.parse().ok()))
+}
+
+/// Parse a core X font name, like `-misc-fixed-medium-r-normal--13-120-75-75-c-70-iso10646-1`
+pub fn xlfd(name: &str) -> Option<Font> {
+    let fields = name.split('-').collect::<Vec<_>>();
+    // The point size is in tenths of a point
+    let size = fields
+        .get(8)
+        .and_then(|size| size.parse::<f64>().ok())
+        .map(|size| size / 10.0);
+    named(fields.get(2)?, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font(name: &str, size: Option<f64>) -> Font {
+        Font {
+            name: ArcStr::from(name),
+            size,
+        }
+    }
+
+    #[test]
+    fn parses_pango() {
+        assert_eq!(
+            pango("Cantarell Bold 11"),
+            Some(font("Cantarell Bold", Some(11.0)))
+        );
+        assert_eq!(pango("Noto Sans 10.5"), Some(font("Noto Sans", Some(10.5))));
+        assert_eq!(pango("Monospace"), Some(font("Monospace", None)));
+        assert_eq!(pango(" "), None);
+    }
+
+    #[test]
+    fn parses_qt() {
+        assert_eq!(
+            qt("Hack,10,-1,5,50,0,0,0,0,0"),
+            Some(font("Hack", Some(10.0)))
+        );
+        assert_eq!(
+            qt("Noto Sans,10,-1,5,400,0,0,0,0,0,0,0,0,0,0,1"),
+            Some(font("Noto Sans", Some(10.0)))
+        );
+        assert_eq!(qt("Hack"), Some(font("Hack", None)));
+    }
+
+    #[test]
+    fn parses_fontconfig() {
+        assert_eq!(
+            fontconfig("Hack:size=11:style=Bold, Noto Color Emoji"),
+            Some(font("Hack", Some(11.0)))
+        );
+        assert_eq!(
+            fontconfig("JetBrains Mono"),
+            Some(font("JetBrains Mono", None))
+        );
+    }
+
+    #[test]
+    fn parses_xlfd() {
+        assert_eq!(
+            xlfd("-misc-fixed-medium-r-normal--13-120-75-75-c-70-iso10646-1"),
+            Some(font("fixed", Some(12.0)))
+        );
+        assert_eq!(xlfd("fixed"), None);
+    }
+}
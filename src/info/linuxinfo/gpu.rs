@@ -0,0 +1,323 @@
+//! GPU detection from `/sys/class/drm`, covering PCI cards and the platform GPUs found in ARM
+//! `SoC`s
+
+use std::{fs, path::Path};
+
+use arcstr::ArcStr;
+use glob::glob;
+use pci_ids::{Device, FromId, Vendor};
+
+use super::sensors;
+use crate::info::{Gpu, GpuKind, Memory};
+
+const AMD: u16 = 0x1002;
+const INTEL: u16 = 0x8086;
+const NVIDIA: u16 = 0x10de;
+
+/// PCI class of a GPU without display outputs, as in laptops where the discrete GPU renders
+/// frames for the integrated one to show
+const CLASS_3D: u32 = 0x0302;
+
+/// Shorter names for vendors whose PCI database names are long-winded
+const VENDOR_NAMES: &[(&str, &str)] = &[
+    ("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD"),
+    ("Intel Corporation", "Intel"),
+    ("NVIDIA Corporation", "NVIDIA"),
+];
+
+/// Devicetree `compatible` prefixes of platform GPUs, as `(prefix, vendor, model prefix)`, so
+/// that `arm,mali-t860` becomes `ARM Mali-T860`
+const PLATFORM_GPUS: &[(&str, &str, &str)] = &[
+    ("arm,mali-", "ARM", "Mali-"),
+    ("qcom,adreno-", "Qualcomm", "Adreno "),
+    ("img,powervr-", "Imagination", "PowerVR "),
+    ("brcm,bcm2711-v3d", "Broadcom", "VideoCore VI"),
+    ("brcm,2711-v3d", "Broadcom", "VideoCore VI"),
+    ("brcm,2712-v3d", "Broadcom", "VideoCore VII"),
+    ("brcm,bcm2835-v3d", "Broadcom", "VideoCore IV"),
+    ("nvidia,", "NVIDIA", ""),
+];
+
+/// Drivers for the firmware framebuffer, which is not a GPU of its own
+const FRAMEBUFFER_DRIVERS: &[&str] = &["simpledrm", "simple-framebuffer", "efifb"];
+
+pub fn gpus() -> Vec<Gpu> {
+    let Ok(cards) = glob("/sys/class/drm/card*") else {
+        return Vec::new();
+    };
+    // Connectors like `card0-DP-1` match too
+    let mut cards = cards
+        .flatten()
+        .filter_map(|card| {
+            let idx = card
+                .file_name()?
+                .to_str()?
+                .strip_prefix("card")?
+                .parse::<u32>();
+            Some((idx.ok()?, card))
+        })
+        .collect::<Vec<_>>();
+    // `card10` sorts before `card2` otherwise
+    cards.sort_unstable_by_key(|(idx, _)| *idx);
+    let devices = cards
+        .into_iter()
+        .map(|(_, card)| card.join("device"))
+        .filter(|device| {
+            !driver(device).is_some_and(|driver| FRAMEBUFFER_DRIVERS.contains(&driver.as_str()))
+        })
+        .collect::<Vec<_>>();
+    let several = devices.len() > 1;
+    devices
+        .iter()
+        .filter_map(|device| {
+            if device.join("vendor").exists() {
+                pci(device, several)
+            } else {
+                platform(device)
+            }
+        })
+        .collect()
+}
+
+fn pci(device: &Path, several: bool) -> Option<Gpu> {
+    let hex = |name: &str| -> Option<u32> {
+        let value = fs::read_to_string(device.join(name)).ok()?;
+        u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+    };
+    let vid = u16::try_from(hex("vendor")?).ok()?;
+    let pid = u16::try_from(hex("device")?).ok()?;
+    let vendor = Vendor::from_id(vid).map(|vendor| {
+        let name = vendor.name();
+        VENDOR_NAMES
+            .iter()
+            .find(|(long, _)| *long == name)
+            .map_or(name, |(_, short)| short)
+    });
+    let model = Device::from_vid_pid(vid, pid).map_or_else(
+        || match vendor {
+            Some(_) => format!("0x{vid:04x}:0x{pid:04x}"),
+            None => format!("Vendor 0x{vid:04x}:0x{pid:04x}"),
+        },
+        |device| device.name().to_owned(),
+    );
+
+    let driver = driver(device);
+    let kind = if hex("class").is_some_and(|class| class >> 8 == CLASS_3D) {
+        Some(GpuKind::Discrete)
+    } else {
+        match vid {
+            // Only APUs report their northbridge voltage
+            AMD if driver.as_deref() == Some("amdgpu") => Some(
+                if glob(&format!("{}/hwmon/hwmon*/in1_input", device.display()))
+                    .is_ok_and(|mut paths| paths.next().is_some())
+                {
+                    GpuKind::Integrated
+                } else {
+                    GpuKind::Discrete
+                },
+            ),
+            INTEL if !model.contains("Arc") => Some(GpuKind::Integrated),
+            INTEL | NVIDIA => Some(GpuKind::Discrete),
+            // Laptops usually boot from the integrated GPU, though desktops with a discrete card
+            // boot from that, so this is only a guess for vendors we can't tell apart otherwise
+            _ if several => hex("boot_vga").map(|boot_vga| {
+                if boot_vga == 1 {
+                    GpuKind::Integrated
+                } else {
+                    GpuKind::Discrete
+                }
+            }),
+            _ => None,
+        }
+    };
+    let vram = (|| {
+        Some(Memory {
+            used: read_u64(&device.join("mem_info_vram_used"))?,
+            total: read_u64(&device.join("mem_info_vram_total"))?,
+            ..Memory::default()
+        })
+    })();
+
+    Some(Gpu {
+        vendor: vendor.map(ArcStr::from),
+        model: ArcStr::from(model),
+        driver: driver.map(ArcStr::from),
+        kind,
+        vram,
+        temperature: sensors::gpu(device),
+    })
+}
+
+/// GPUs that are described by the devicetree instead of sitting on a bus that can be probed
+fn platform(device: &Path) -> Option<Gpu> {
+    let compatible = fs::read(device.join("of_node/compatible")).ok()?;
+    let compatible = compatible
+        .split(|byte| *byte == 0)
+        .filter_map(|entry| std::str::from_utf8(entry).ok())
+        .filter(|entry| !entry.is_empty())
+        .collect::<Vec<_>>();
+    let (vendor, model) = compatible
+        .iter()
+        .find_map(|entry| {
+            PLATFORM_GPUS.iter().find_map(|(prefix, vendor, model)| {
+                let rest = entry.strip_prefix(prefix)?;
+                // Drop revision suffixes, e.g. the `.2` in `qcom,adreno-630.2`
+                let rest = rest.split('.').next().unwrap_or_default().to_uppercase();
+                Some((Some(*vendor), format!("{model}{rest}").trim().to_owned()))
+            })
+        })
+        .or_else(|| Some((None, (*compatible.first()?).to_owned())))?;
+    Some(Gpu {
+        vendor: vendor.map(ArcStr::from),
+        model: ArcStr::from(model),
+        driver: driver(device).map(ArcStr::from),
+        kind: Some(GpuKind::Integrated),
+        vram: None,
+        temperature: sensors::gpu(device),
+    })
+}
+
+fn driver(device: &Path) -> Option<String> {
+    let driver = fs::read_link(device.join("driver")).ok()?;
+    Some(driver.file_name()?.to_str()?.to_owned())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    fn device(sysfs: &FakeFs, name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        for (file, contents) in files {
+            sysfs.write(&format!("{name}/{file}"), format!("{contents}\n"));
+        }
+        sysfs.join(name)
+    }
+
+    #[test]
+    fn falls_back_to_ids() {
+        let sysfs = FakeFs::new();
+        let unknown = device(
+            &sysfs,
+            "unknown",
+            &[
+                ("vendor", "0x1234"),
+                ("device", "0x5678"),
+                ("class", "0x030000"),
+            ],
+        );
+        let gpu = pci(&unknown, false).unwrap();
+        assert_eq!(gpu.vendor, None);
+        assert_eq!(gpu.to_string(), "Vendor 0x1234:0x5678");
+
+        let new_amd = device(
+            &sysfs,
+            "new_amd",
+            &[
+                ("vendor", "0x1002"),
+                ("device", "0xfffe"),
+                ("class", "0x030000"),
+            ],
+        );
+        assert_eq!(
+            pci(&new_amd, false).unwrap().to_string(),
+            "AMD 0x1002:0xfffe"
+        );
+    }
+
+    #[test]
+    fn classifies_by_vendor_before_boot_vga() {
+        let sysfs = FakeFs::new();
+        // A desktop whose firmware boots from the discrete card
+        let intel = device(
+            &sysfs,
+            "intel",
+            &[
+                ("vendor", "0x8086"),
+                ("device", "0x9a49"),
+                ("class", "0x030000"),
+                ("boot_vga", "0"),
+            ],
+        );
+        let amd = device(
+            &sysfs,
+            "amd",
+            &[
+                ("vendor", "0x1002"),
+                ("device", "0x73bf"),
+                ("class", "0x030000"),
+                ("boot_vga", "1"),
+            ],
+        );
+        sysfs.symlink("drivers/amdgpu", "amd/driver");
+        assert_eq!(pci(&intel, true).unwrap().kind, Some(GpuKind::Integrated));
+        assert_eq!(pci(&amd, true).unwrap().kind, Some(GpuKind::Discrete));
+        assert_eq!(pci(&intel, false).unwrap().kind, Some(GpuKind::Integrated));
+    }
+
+    #[test]
+    fn amd_apus_report_northbridge_voltage() {
+        let sysfs = FakeFs::new();
+        let apu = device(
+            &sysfs,
+            "apu",
+            &[
+                ("vendor", "0x1002"),
+                ("device", "0x1638"),
+                ("class", "0x030000"),
+                ("boot_vga", "1"),
+                ("hwmon/hwmon4/in1_input", "1381"),
+            ],
+        );
+        sysfs.symlink("drivers/amdgpu", "apu/driver");
+        assert_eq!(pci(&apu, true).unwrap().kind, Some(GpuKind::Integrated));
+    }
+
+    #[test]
+    fn falls_back_to_boot_vga_for_other_vendors() {
+        let sysfs = FakeFs::new();
+        let booted = device(
+            &sysfs,
+            "booted",
+            &[
+                ("vendor", "0x1234"),
+                ("device", "0x1111"),
+                ("class", "0x030000"),
+                ("boot_vga", "1"),
+            ],
+        );
+        let other = device(
+            &sysfs,
+            "other",
+            &[
+                ("vendor", "0x1234"),
+                ("device", "0x2222"),
+                ("class", "0x030000"),
+                ("boot_vga", "0"),
+            ],
+        );
+        assert_eq!(pci(&booted, true).unwrap().kind, Some(GpuKind::Integrated));
+        assert_eq!(pci(&other, true).unwrap().kind, Some(GpuKind::Discrete));
+        assert_eq!(pci(&booted, false).unwrap().kind, None);
+    }
+
+    #[test]
+    fn render_only_gpus_are_discrete() {
+        let sysfs = FakeFs::new();
+        let nvidia = device(
+            &sysfs,
+            "nvidia",
+            &[
+                ("vendor", "0x10de"),
+                ("device", "0x25a2"),
+                ("class", "0x030200"),
+                ("boot_vga", "1"),
+            ],
+        );
+        assert_eq!(pci(&nvidia, true).unwrap().kind, Some(GpuKind::Discrete));
+    }
+}
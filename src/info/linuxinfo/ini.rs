@@ -0,0 +1,95 @@
+//! A forgiving reader for the INI-style config files used by GTK, KDE and various terminals
+
+use std::{fs, path::Path};
+
+use rustc_hash::FxHashMap;
+
+/// Sections of `key=value` pairs, with any pairs before the first section under `""`
+#[derive(Debug, Clone, Default)]
+pub struct Ini(FxHashMap<String, FxHashMap<String, String>>);
+
+impl Ini {
+    pub fn read(path: impl AsRef<Path>) -> Option<Self> {
+        Some(Self::parse(&fs::read_to_string(path).ok()?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut sections = FxHashMap::<String, FxHashMap<String, String>>::default();
+        let mut section = String::new();
+        for line in text.lines().map(str::trim) {
+            if line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                name.clone_into(&mut section);
+            } else if let Some((key, value)) = line.split_once('=') {
+                sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.trim().to_owned(), unquote(value.trim()).to_owned());
+            }
+        }
+        Self(sections)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.0
+            .get(section)?
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sections() {
+        let ini = Ini::parse(
+            "\
+# written by kitty-themes
+font_family=ignored
+[Settings]
+gtk-theme-name = \"Adwaita-dark\"
+gtk-font-name=Cantarell 11
+; gtk-icon-theme-name=Papirus
+gtk-cursor-theme-size=
+
+[Appearance]
+Font=Hack Nerd Font Mono,12,-1,5,50,0,0,0,0,0
+",
+        );
+        assert_eq!(ini.get("", "font_family"), Some("ignored"));
+        assert_eq!(ini.get("Settings", "gtk-theme-name"), Some("Adwaita-dark"));
+        assert_eq!(ini.get("Settings", "gtk-font-name"), Some("Cantarell 11"));
+        assert_eq!(ini.get("Settings", "gtk-icon-theme-name"), None);
+        assert_eq!(ini.get("Settings", "gtk-cursor-theme-size"), None);
+        assert_eq!(
+            ini.get("Appearance", "Font"),
+            Some("Hack Nerd Font Mono,12,-1,5,50,0,0,0,0,0")
+        );
+        assert_eq!(ini.get("Appearance", "gtk-theme-name"), None);
+        assert_eq!(ini.get("Missing", "Font"), None);
+    }
+
+    #[test]
+    fn keeps_equals_signs_in_values() {
+        let ini = Ini::parse("[General]\nCommand=env TERM=xterm-256color bash\n");
+        assert_eq!(
+            ini.get("General", "Command"),
+            Some("env TERM=xterm-256color bash")
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Memory and swap usage, from `/proc/meminfo`, `/proc/swaps` and zram's statistics
+
+use std::fs;
+
+use arcstr::ArcStr;
+use rustc_hash::FxHashMap;
+
+use crate::info::{Memory, Swap};
+
+pub fn memory() -> Option<Memory> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    // Values are in KiB, apart from page counts
+    let fields = meminfo
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim();
+            let (value, unit) = value.split_once(' ').unwrap_or((value, ""));
+            let value = value.parse::<u64>().ok()?;
+            Some((key, if unit == "kB" { value << 10 } else { value }))
+        })
+        .collect::<FxHashMap<_, _>>();
+    let total = *fields.get("MemTotal")?;
+    let available = fields
+        .get("MemAvailable")
+        .copied()
+        // Kernels before 3.14 don't estimate what's available
+        .or_else(|| {
+            Some(fields.get("MemFree")? + fields.get("Buffers")? + fields.get("Cached")?)
+        })?;
+    Some(Memory {
+        used: total.saturating_sub(available),
+        total,
+        buffers: fields.get("Buffers").copied(),
+        cached: fields.get("Cached").copied(),
+        shared: fields.get("Shmem").copied(),
+        huge_pages: fields
+            .get("HugePages_Total")
+            .zip(fields.get("Hugepagesize"))
+            .map(|(count, size)| count * size),
+    })
+}
+
+/// Swap partitions and files combined, followed by each zram device
+pub fn swap() -> Vec<Swap> {
+    let Ok(swaps) = fs::read_to_string("/proc/swaps") else {
+        return Vec::new();
+    };
+    let mut combined: Option<Swap> = None;
+    let mut zram = Vec::new();
+    // `Filename Type Size Used Priority`, with sizes in KiB
+    for line in swaps.lines().skip(1) {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (Some(name), Some(Ok(total)), Some(Ok(used))) = (
+            fields.first(),
+            fields.get(2).map(|size| size.parse::<u64>()),
+            fields.get(3).map(|used| used.parse::<u64>()),
+        ) else {
+            continue;
+        };
+        let (total, used) = (total << 10, used << 10);
+        if let Some(device) = name
+            .strip_prefix("/dev/")
+            .filter(|device| device.starts_with("zram"))
+        {
+            // `orig_data_size compr_data_size mem_used_total ...`, in bytes
+            let stats = fs::read_to_string(format!("/sys/block/{device}/mm_stat")).ok();
+            let stats = stats
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|value| value.parse::<u64>().ok())
+                .collect::<Vec<_>>();
+            zram.push(Swap {
+                zram: Some(ArcStr::from(device)),
+                used,
+                total,
+                original: stats.first().copied().flatten(),
+                compressed: stats.get(1).copied().flatten(),
+            });
+        } else {
+            let swap = combined.get_or_insert(Swap {
+                zram: None,
+                used: 0,
+                total: 0,
+                original: None,
+                compressed: None,
+            });
+            swap.used += used;
+            swap.total += total;
+        }
+    }
+    combined.into_iter().chain(zram).collect()
+}
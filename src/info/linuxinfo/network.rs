@@ -0,0 +1,183 @@
+//! Network interfaces and their addresses, from `getifaddrs(3)` and `/sys/class/net`
+
+use std::{
+    ffi::CStr,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+use arcstr::ArcStr;
+use libc::{AF_INET, AF_INET6, IFF_LOOPBACK, IFF_RUNNING, IFF_UP};
+
+use crate::{
+    config::NetworkConfig,
+    info::{Interface, InterfaceKind, IpNet},
+};
+
+/// `ARPHRD_NONE`, the hardware type of tun devices and `WireGuard`
+const ARPHRD_NONE: &str = "65534";
+
+/// Name prefixes of VPN interfaces that look like any other virtual interface in sysfs
+const VPN_PREFIXES: &[&str] = &["tun", "tap", "wg", "tailscale", "zt", "ppp", "nordlynx"];
+
+pub fn interfaces(config: &NetworkConfig) -> Vec<Interface> {
+    let mut interfaces = Vec::<Interface>::new();
+    for (name, addr) in addresses() {
+        if addr.addr.is_ipv6() && !config.show_ipv6 {
+            continue;
+        }
+        if let Some(interface) = interfaces
+            .iter_mut()
+            .find(|interface| interface.name == name)
+        {
+            interface.addrs.push(addr);
+            continue;
+        }
+        let sysfs = Path::new("/sys/class/net").join(&name);
+        let kind = kind(&sysfs, &name);
+        if !config.show_virtual
+            && matches!(kind, Some(InterfaceKind::Bridge | InterfaceKind::Virtual))
+        {
+            continue;
+        }
+        let read = |file: &str| {
+            fs::read_to_string(sysfs.join(file))
+                .ok()
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        };
+        // tun and veth devices report a made-up link speed
+        let hardware = matches!(
+            kind,
+            Some(InterfaceKind::Ethernet | InterfaceKind::Wifi) | None
+        );
+        let link = |file: &str| read(file).filter(|_| hardware);
+        interfaces.push(Interface {
+            name: ArcStr::from(name),
+            kind,
+            addrs: vec![addr],
+            mac: read("address")
+                .filter(|mac| mac != "00:00:00:00:00:00")
+                .map(ArcStr::from),
+            // -1 if the link is down or the driver doesn't know, as for most Wi-Fi cards
+            speed: link("speed").and_then(|speed| speed.parse().ok()),
+            duplex: link("duplex")
+                .filter(|duplex| duplex != "unknown")
+                .map(ArcStr::from),
+        });
+    }
+    for interface in &mut interfaces {
+        interface.addrs.sort_by_key(|addr| addr.addr.is_ipv6());
+    }
+    interfaces
+}
+
+/// The names of VPN interfaces that are up
+pub fn vpns() -> Vec<ArcStr> {
+    let Ok(interfaces) = fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    let mut vpns = interfaces
+        .flatten()
+        .filter_map(|interface| {
+            let name = interface.file_name().into_string().ok()?;
+            let sysfs = interface.path();
+            // tun devices are `unknown` while a program has them open, and `down` otherwise
+            let down = fs::read_to_string(sysfs.join("operstate"))
+                .is_ok_and(|state| state.trim() == "down");
+            (!down && kind(&sysfs, &name) == Some(InterfaceKind::Vpn)).then(|| ArcStr::from(name))
+        })
+        .collect::<Vec<_>>();
+    vpns.sort();
+    vpns
+}
+
+/// The addresses of every interface that's up, apart from loopback, by interface name
+fn addresses() -> Vec<(String, IpNet)> {
+    let mut addrs = std::ptr::null_mut::<libc::ifaddrs>();
+    // SAFETY: `addrs` is only read if the call succeeds
+    if unsafe { libc::getifaddrs(std::ptr::addr_of_mut!(addrs)) } != 0 {
+        return Vec::new();
+    }
+    let mut res = Vec::new();
+    let mut current = addrs;
+    // SAFETY: the list is valid until it's freed below, and each address and netmask is the
+    // `sockaddr_*` that its family says
+    // A prefix is at most 128 bits long
+    #[allow(clippy::cast_ptr_alignment, clippy::cast_possible_truncation)]
+    unsafe {
+        while let Some(ifaddr) = current.as_ref() {
+            current = ifaddr.ifa_next;
+            let up = (IFF_UP | IFF_RUNNING) as u32;
+            if ifaddr.ifa_addr.is_null()
+                || ifaddr.ifa_flags & up != up
+                || ifaddr.ifa_flags & IFF_LOOPBACK as u32 != 0
+            {
+                continue;
+            }
+            let netmask = ifaddr.ifa_netmask;
+            let addr = match i32::from((*ifaddr.ifa_addr).sa_family) {
+                AF_INET => {
+                    let addr = &*ifaddr.ifa_addr.cast::<libc::sockaddr_in>();
+                    let prefix_len = netmask.as_ref().map_or(32, |_| {
+                        (*netmask.cast::<libc::sockaddr_in>())
+                            .sin_addr
+                            .s_addr
+                            .count_ones()
+                    });
+                    IpNet {
+                        addr: IpAddr::from(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr))),
+                        prefix_len: prefix_len as u8,
+                    }
+                }
+                AF_INET6 => {
+                    let addr = &*ifaddr.ifa_addr.cast::<libc::sockaddr_in6>();
+                    let prefix_len = netmask.as_ref().map_or(128, |_| {
+                        (*netmask.cast::<libc::sockaddr_in6>())
+                            .sin6_addr
+                            .s6_addr
+                            .iter()
+                            .map(|byte| byte.count_ones())
+                            .sum()
+                    });
+                    IpNet {
+                        addr: IpAddr::from(Ipv6Addr::from(addr.sin6_addr.s6_addr)),
+                        prefix_len: prefix_len as u8,
+                    }
+                }
+                _ => continue,
+            };
+            let name = CStr::from_ptr(ifaddr.ifa_name)
+                .to_string_lossy()
+                .into_owned();
+            res.push((name, addr));
+        }
+        libc::freeifaddrs(addrs);
+    }
+    res
+}
+
+/// What kind of interface `name` is, from its directory in `/sys/class/net`
+fn kind(sysfs: &Path, name: &str) -> Option<InterfaceKind> {
+    let uevent = fs::read_to_string(sysfs.join("uevent")).ok()?;
+    let devtype = uevent
+        .lines()
+        .find_map(|line| line.strip_prefix("DEVTYPE="));
+    let kind = if sysfs.join("wireless").exists() || devtype == Some("wlan") {
+        InterfaceKind::Wifi
+    } else if devtype == Some("bridge") {
+        InterfaceKind::Bridge
+    } else if devtype == Some("wireguard")
+        || fs::read_to_string(sysfs.join("type")).is_ok_and(|kind| kind.trim() == ARPHRD_NONE)
+        || VPN_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+    {
+        InterfaceKind::Vpn
+    } else if sysfs.join("device").exists() {
+        // Only interfaces backed by hardware have a device
+        InterfaceKind::Ethernet
+    } else {
+        InterfaceKind::Virtual
+    };
+    Some(kind)
+}
@@ -0,0 +1,190 @@
+//! The frequency a wireless interface is connected on, from nl80211 over generic netlink
+//!
+//! Neither sysfs nor the wireless extensions in `/proc/net/wireless` report it. See `genetlink.h`
+//! and `nl80211.h`.
+
+use std::{
+    ffi::CString,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+
+const NLMSG_ERROR: u16 = 2;
+const NLM_F_REQUEST: u16 = 1;
+/// `nlmsghdr` followed by `genlmsghdr`
+const HEADER_LEN: usize = 20;
+/// The top bits of an attribute's type are flags
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+struct Socket(OwnedFd);
+
+impl Socket {
+    fn open() -> Option<Self> {
+        // SAFETY: no pointers are involved
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        // SAFETY: the descriptor was just opened, and nothing else owns it
+        (fd >= 0).then(|| Self(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    /// Send a request with the given attributes, and return the attributes of the reply
+    fn request(&self, family: u16, cmd: u8, attrs: &[(u16, &[u8])]) -> Option<Vec<u8>> {
+        let mut msg = Vec::with_capacity(64);
+        // The length is filled in once it's known, and the kernel fills in the port ID
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&family.to_ne_bytes());
+        msg.extend_from_slice(&NLM_F_REQUEST.to_ne_bytes());
+        msg.extend_from_slice(&1u32.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&[cmd, 1, 0, 0]);
+        for (kind, payload) in attrs {
+            let len = u16::try_from(4 + payload.len()).ok()?;
+            msg.extend_from_slice(&len.to_ne_bytes());
+            msg.extend_from_slice(&kind.to_ne_bytes());
+            msg.extend_from_slice(payload);
+            msg.resize(msg.len().next_multiple_of(4), 0);
+        }
+        let len = u32::try_from(msg.len()).ok()?.to_ne_bytes();
+        msg[..4].copy_from_slice(&len);
+
+        let fd = self.0.as_raw_fd();
+        // SAFETY: the buffer is valid for its length
+        if unsafe { libc::send(fd, msg.as_ptr().cast(), msg.len(), 0) } < 0 {
+            return None;
+        }
+        let mut reply = vec![0u8; 8192];
+        // SAFETY: the buffer is valid for its length
+        let received = unsafe { libc::recv(fd, reply.as_mut_ptr().cast(), reply.len(), 0) };
+        reply.truncate(usize::try_from(received).ok()?);
+
+        let len = u32::from_ne_bytes(reply.get(..4)?.try_into().ok()?) as usize;
+        let kind = u16::from_ne_bytes(reply.get(4..6)?.try_into().ok()?);
+        if kind == NLMSG_ERROR {
+            return None;
+        }
+        reply.truncate(len);
+        Some(reply.get(HEADER_LEN..)?.to_vec())
+    }
+}
+
+/// The payload of the first attribute of type `wanted`
+fn attribute(mut attrs: &[u8], wanted: u16) -> Option<&[u8]> {
+    while attrs.len() >= 4 {
+        let len = usize::from(u16::from_ne_bytes([attrs[0], attrs[1]]));
+        let kind = u16::from_ne_bytes([attrs[2], attrs[3]]) & NLA_TYPE_MASK;
+        if len < 4 || len > attrs.len() {
+            return None;
+        }
+        if kind == wanted {
+            return Some(&attrs[4..len]);
+        }
+        attrs = attrs.get(len.next_multiple_of(4)..).unwrap_or_default();
+    }
+    None
+}
+
+/// In MHz, or `None` if `interface` isn't connected
+pub fn frequency(interface: &str) -> Option<u32> {
+    let socket = Socket::open()?;
+    let reply = socket.request(
+        GENL_ID_CTRL,
+        CTRL_CMD_GETFAMILY,
+        &[(CTRL_ATTR_FAMILY_NAME, b"nl80211\0")],
+    )?;
+    let family = u16::from_ne_bytes(attribute(&reply, CTRL_ATTR_FAMILY_ID)?.try_into().ok()?);
+
+    let name = CString::new(interface).ok()?;
+    // SAFETY: `name` is NUL-terminated
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if index == 0 {
+        return None;
+    }
+    let reply = socket.request(
+        family,
+        NL80211_CMD_GET_INTERFACE,
+        &[(NL80211_ATTR_IFINDEX, &index.to_ne_bytes())],
+    )?;
+    let frequency = attribute(&reply, NL80211_ATTR_WIPHY_FREQ)?;
+    Some(u32::from_ne_bytes(frequency.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An attribute with its header, padded to a multiple of 4 bytes
+    fn attr(kind: u16, payload: &[u8]) -> Vec<u8> {
+        let len = u16::try_from(4 + payload.len()).unwrap();
+        let mut attr = [len.to_ne_bytes(), kind.to_ne_bytes()].concat();
+        attr.extend_from_slice(payload);
+        attr.resize(attr.len().next_multiple_of(4), 0);
+        attr
+    }
+
+    #[test]
+    fn skips_padding() {
+        let attrs = [
+            attr(2, b"nl80211\0"),
+            attr(7, b"abcde"),
+            attr(1, &[0x1c, 0]),
+        ]
+        .concat();
+        assert_eq!(attribute(&attrs, 2), Some(&b"nl80211\0"[..]));
+        assert_eq!(attribute(&attrs, 7), Some(&b"abcde"[..]));
+        assert_eq!(attribute(&attrs, 1), Some(&[0x1c, 0][..]));
+        assert_eq!(attribute(&attrs, 3), None);
+    }
+
+    #[test]
+    fn ignores_type_flags() {
+        const NLA_F_NESTED: u16 = 0x8000;
+        const NLA_F_NET_BYTEORDER: u16 = 0x4000;
+        let attrs = [
+            attr(NLA_F_NESTED | CTRL_ATTR_FAMILY_NAME, b"nest"),
+            attr(
+                NLA_F_NET_BYTEORDER | NL80211_ATTR_WIPHY_FREQ,
+                &2437u32.to_be_bytes(),
+            ),
+        ]
+        .concat();
+        assert_eq!(attribute(&attrs, CTRL_ATTR_FAMILY_NAME), Some(&b"nest"[..]));
+        assert_eq!(
+            attribute(&attrs, NL80211_ATTR_WIPHY_FREQ),
+            Some(&2437u32.to_be_bytes()[..])
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_attributes() {
+        let mut attrs = attr(38, &2437u32.to_ne_bytes());
+        attrs.truncate(6);
+        assert_eq!(attribute(&attrs, 38), None);
+        // Shorter than its own header
+        let attrs = [2u16.to_ne_bytes(), 38u16.to_ne_bytes()].concat();
+        assert_eq!(attribute(&attrs, 38), None);
+        // A trailing partial header is ignored
+        let attrs = [attr(1, b"ab"), vec![8, 0]].concat();
+        assert_eq!(attribute(&attrs, 38), None);
+        assert_eq!(attribute(&attrs, 1), Some(&b"ab"[..]));
+    }
+
+    #[test]
+    fn reads_unpadded_final_attribute() {
+        let mut attrs = attr(38, b"abc");
+        attrs.truncate(7);
+        assert_eq!(attribute(&attrs, 38), Some(&b"abc"[..]));
+    }
+}
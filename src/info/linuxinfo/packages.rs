@@ -0,0 +1,177 @@
+//! Counts installed packages by reading each package manager's local database directly, rather
+//! than spawning the package managers themselves
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use glob::glob;
+use rustc_hash::FxHashSet;
+
+use super::sqlite;
+use crate::info::PackageCount;
+
+/// Count packages for every package manager with a local database, skipping any without
+/// packages installed
+pub fn packages() -> Vec<PackageCount> {
+    [
+        ("dpkg", dpkg as fn() -> Option<u64>),
+        ("rpm", rpm),
+        ("pacman", pacman),
+        ("nix", nix),
+        ("flatpak", flatpak),
+        ("snap", snap),
+    ]
+    .into_iter()
+    .filter_map(|(manager, count)| {
+        count()
+            .filter(|count| *count > 0)
+            .map(|count| PackageCount { manager, count })
+    })
+    .collect()
+}
+
+fn dpkg() -> Option<u64> {
+    let status = fs::read_to_string("/var/lib/dpkg/status").ok()?;
+    Some(installed(&status))
+}
+
+/// `Status: <want> <flag> <status>`, where held packages want `hold` rather than `install`
+fn installed(status: &str) -> u64 {
+    status
+        .lines()
+        .filter_map(|line| line.strip_prefix("Status: "))
+        .filter(|status| status.split_whitespace().skip(1).eq(["ok", "installed"]))
+        .count() as u64
+}
+
+fn rpm() -> Option<u64> {
+    [
+        "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+        "/var/lib/rpm/rpmdb.sqlite",
+    ]
+    .into_iter()
+    .find_map(|path| sqlite::count_rows(Path::new(path), "Packages"))
+}
+
+fn pacman() -> Option<u64> {
+    Some(
+        fs::read_dir("/var/lib/pacman/local")
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+            .count() as u64,
+    )
+}
+
+/// Nix profiles are trees of symlinks into the store, so count the distinct store paths their
+/// executables point to
+fn nix() -> Option<u64> {
+    let mut profiles = vec![
+        PathBuf::from("/nix/var/nix/profiles/default"),
+        PathBuf::from("/nix/var/nix/profiles/system/sw"),
+    ];
+    if let Ok(paths) = glob("/nix/var/nix/profiles/per-user/*/profile") {
+        profiles.extend(paths.filter_map(Result::ok));
+    }
+    if let Ok(home) = env::var("HOME") {
+        profiles.push(Path::new(&home).join(".nix-profile"));
+    }
+    if let Ok(user) = env::var("USER") {
+        profiles.push(Path::new("/etc/profiles/per-user").join(user));
+    }
+
+    let mut store_paths = FxHashSet::default();
+    for bin in profiles.iter().map(|profile| profile.join("bin")) {
+        let Ok(entries) = fs::read_dir(&bin) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            if let Some(package) = fs::read_link(entry.path())
+                .ok()
+                .as_deref()
+                .and_then(store_path)
+            {
+                store_paths.insert(package);
+            }
+        }
+    }
+    (!store_paths.is_empty()).then_some(store_paths.len() as u64)
+}
+
+/// The top-level `/nix/store/<hash>-<name>` directory containing `path`
+fn store_path(path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix("/nix/store").ok()?;
+    Some(Path::new("/nix/store").join(relative.components().next()?))
+}
+
+/// Flatpak deploys each app and runtime to `<kind>/<name>/<arch>/<branch>`
+fn flatpak() -> Option<u64> {
+    let mut installations = vec![PathBuf::from("/var/lib/flatpak")];
+    if let Ok(home) = env::var("HOME") {
+        installations.push(Path::new(&home).join(".local/share/flatpak"));
+    }
+    installations.retain(|installation| installation.is_dir());
+    if installations.is_empty() {
+        return None;
+    }
+    let count = installations
+        .iter()
+        .flat_map(|installation| {
+            ["app", "runtime"].map(|kind| installation.join(kind).join("*/*/*"))
+        })
+        .filter_map(|pattern| glob(pattern.to_str()?).ok())
+        .flat_map(|paths| paths.filter_map(Result::ok))
+        // `<name>/current` is a symlink to the active `<arch>/<branch>`
+        .filter(|path| {
+            path.parent()
+                .and_then(Path::file_name)
+                .is_some_and(|arch| arch != "current")
+        })
+        .count();
+    Some(count as u64)
+}
+
+/// Snaps are stored as `<name>_<revision>.snap`, with old revisions kept around
+fn snap() -> Option<u64> {
+    let names = fs::read_dir("/var/lib/snapd/snaps")
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let name = name.to_str()?.strip_suffix(".snap")?;
+            Some(
+                name.rsplit_once('_')
+                    .map_or(name, |(name, _)| name)
+                    .to_owned(),
+            )
+        })
+        .collect::<FxHashSet<String>>();
+    Some(names.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_installed_and_held_dpkg_packages() {
+        let status = "\
+Package: bash
+Status: install ok installed
+Priority: required
+
+Package: linux-image-6.1.0-18-amd64
+Status: hold ok installed
+
+Package: old-package
+Status: deinstall ok config-files
+
+Package: broken
+Status: install reinstreq half-installed
+Description: Status: install ok installed
+";
+        assert_eq!(installed(status), 2);
+    }
+}
@@ -0,0 +1,190 @@
+//! Helpers for inspecting other processes through `/proc`
+
+use std::{
+    fs, iter,
+    os::unix::fs::MetadataExt,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "tcsh", "csh", "nu", "elvish", "xonsh",
+    "ion", "pwsh",
+];
+
+/// Programs that commonly sit between a terminal and what it runs, without being terminals
+pub const WRAPPERS: &[&str] = &[
+    "sudo",
+    "su",
+    "doas",
+    "env",
+    "cargo",
+    "script",
+    "time",
+    "timeout",
+    "nohup",
+    "setsid",
+    "nice",
+    "stdbuf",
+    "xargs",
+    "ssh",
+    "sshd",
+    "sshd-session",
+    "mosh-server",
+    "tmux",
+    "tmux: client",
+    "tmux: server",
+    "screen",
+    "SCREEN",
+    "zellij",
+    "strace",
+    "gdb",
+    "valgrind",
+    "watch",
+    "nix-shell",
+    "nix",
+    "direnv",
+    "login",
+    "systemd",
+];
+
+/// The kernel truncates `comm` to this many bytes
+const COMM_LEN: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    /// The executable name, as reported by `/proc/<pid>/stat`
+    pub comm: String,
+}
+
+impl Process {
+    #[allow(clippy::similar_names)]
+    pub fn get(pid: u32) -> Option<Self> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // `comm` is in parentheses and may itself contain spaces or parentheses
+        let (_, rest) = stat.split_once(" (")?;
+        let (comm, fields) = rest.rsplit_once(") ")?;
+        // After `comm` come `state` and then `ppid`
+        let ppid = fields.split_ascii_whitespace().nth(1)?.parse().ok()?;
+        Some(Self {
+            pid,
+            ppid,
+            comm: comm.to_owned(),
+        })
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        (self.ppid != 0).then(|| Self::get(self.ppid)).flatten()
+    }
+
+    /// `comm` with any NixOS wrapper naming removed
+    pub fn name(&self) -> &str {
+        unwrapped(&self.comm)
+    }
+
+    /// Whether this process is running `name`, allowing for `comm` being truncated
+    pub fn is(&self, name: &str) -> bool {
+        let name = name.get(..COMM_LEN).unwrap_or(name);
+        self.name() == name || self.comm == name
+    }
+
+    /// This process, its parent, and so on up to (but not including) `init`
+    pub fn lineage(self) -> impl Iterator<Item = Self> {
+        iter::successors(Some(self), Self::parent).take_while(|process| process.pid != 1)
+    }
+
+    /// The path to the running executable, resolving any symlinks like `/bin/sh`. The kernel
+    /// appends ` (deleted)` once the file has been replaced, e.g. by an upgrade, which is left off.
+    pub fn exe(&self) -> Option<PathBuf> {
+        let path = fs::read_link(self.exe_link()).ok()?;
+        let replaced = path
+            .to_str()
+            .and_then(|path| path.strip_suffix(" (deleted)"))
+            .map(PathBuf::from);
+        Some(replaced.unwrap_or(path))
+    }
+
+    /// A link to the running executable, which can still be opened once the file is replaced
+    pub fn exe_link(&self) -> PathBuf {
+        PathBuf::from(format!("/proc/{}/exe", self.pid))
+    }
+
+    /// The user the process runs as
+    pub fn uid(&self) -> Option<u32> {
+        fs::metadata(format!("/proc/{}", self.pid))
+            .ok()
+            .map(|proc| proc.uid())
+    }
+
+    /// The terminal the process reads from, e.g. `/dev/pts/3`
+    pub fn tty(&self) -> Option<PathBuf> {
+        fs::read_link(format!("/proc/{}/fd/0", self.pid)).ok()
+    }
+
+    /// The command line arguments, including `argv[0]`
+    pub fn cmdline(&self) -> Vec<String> {
+        Self::nul_separated(&format!("/proc/{}/cmdline", self.pid))
+    }
+
+    fn nul_separated(path: &str) -> Vec<String> {
+        fs::read(path)
+            .map(|data| {
+                data.split(|byte| *byte == 0)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| String::from_utf8_lossy(item).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// NixOS wraps programs as `.<name>-wrapped`, which may be cut short by the length limit on `comm`
+pub fn unwrapped(comm: &str) -> &str {
+    let Some(name) = comm.strip_prefix('.') else {
+        return comm;
+    };
+    name.rfind('-')
+        .filter(|idx| "-wrapped".starts_with(&name[*idx..]))
+        .map_or(name, |idx| &name[..idx])
+}
+
+/// Our parent, its parent, and so on up to (but not including) `init`
+pub fn ancestors() -> impl Iterator<Item = Process> {
+    Process::get(std::os::unix::process::parent_id())
+        .into_iter()
+        .flat_map(Process::lineage)
+}
+
+/// Every process we can see
+pub fn all() -> impl Iterator<Item = Process> {
+    fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .filter_map(Process::get)
+}
+
+/// Run `command` and collect what it prints, giving up if it takes longer than `timeout`
+pub fn output(mut command: Command, timeout: Duration) -> Option<Vec<u8>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let start = Instant::now();
+    while child.try_wait().ok()?.is_none() {
+        if start.elapsed() > timeout {
+            child.kill().ok();
+            child.wait().ok();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    Some(child.wait_with_output().ok()?.stdout)
+}
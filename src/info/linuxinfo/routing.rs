@@ -0,0 +1,176 @@
+//! Default gateways and DNS servers, from `/proc/net` and `resolv.conf`
+
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use arcstr::ArcStr;
+
+use super::network;
+use crate::info::{Gateway, Routing};
+
+const RTF_UP: u32 = 0x1;
+const RTF_GATEWAY: u32 = 0x2;
+
+/// The address systemd-resolved listens on, which only forwards to the real servers
+const RESOLVED_STUB: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 53));
+
+pub fn routing() -> Option<Routing> {
+    let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+    let routing = Routing {
+        gateways: ipv4_gateways(&read("/proc/net/route"))
+            .into_iter()
+            .chain(ipv6_gateways(&read("/proc/net/ipv6_route")))
+            .collect(),
+        dns: dns(),
+        vpns: network::vpns(),
+    };
+    (!routing.gateways.is_empty() || !routing.dns.is_empty() || !routing.vpns.is_empty())
+        .then_some(routing)
+}
+
+/// `Iface Destination Gateway Flags RefCnt Use Metric Mask ...`, with addresses in hex in the
+/// kernel's byte order
+fn ipv4_gateways(routes: &str) -> Vec<Gateway> {
+    let mut gateways = routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let hex = |idx: usize| u32::from_str_radix(fields.get(idx)?, 16).ok();
+            let flags = hex(3)?;
+            if hex(1)? != 0
+                || hex(7)? != 0
+                || flags & (RTF_UP | RTF_GATEWAY) != RTF_UP | RTF_GATEWAY
+            {
+                return None;
+            }
+            let metric = fields.get(6)?.parse::<u32>().ok()?;
+            let gateway = Gateway {
+                addr: IpAddr::from(Ipv4Addr::from(hex(2)?.to_ne_bytes())),
+                interface: ArcStr::from(*fields.first()?),
+            };
+            Some((metric, gateway))
+        })
+        .collect::<Vec<_>>();
+    gateways.sort_by_key(|(metric, _)| *metric);
+    gateways.into_iter().map(|(_, gateway)| gateway).collect()
+}
+
+/// `dest dest_len src src_len next_hop metric refcnt use flags iface`, with addresses in hex in
+/// network byte order
+fn ipv6_gateways(routes: &str) -> Vec<Gateway> {
+    let mut gateways = routes
+        .lines()
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let hex = |idx: usize| u128::from_str_radix(fields.get(idx)?, 16).ok();
+            let flags = u32::from_str_radix(fields.get(8)?, 16).ok()?;
+            if hex(0)? != 0
+                || hex(1)? != 0
+                || flags & (RTF_UP | RTF_GATEWAY) != RTF_UP | RTF_GATEWAY
+            {
+                return None;
+            }
+            let metric = u32::from_str_radix(fields.get(5)?, 16).ok()?;
+            let gateway = Gateway {
+                addr: IpAddr::from(Ipv6Addr::from(hex(4)?)),
+                interface: ArcStr::from(*fields.get(9)?),
+            };
+            Some((metric, gateway))
+        })
+        .collect::<Vec<_>>();
+    gateways.sort_by_key(|(metric, _)| *metric);
+    gateways.into_iter().map(|(_, gateway)| gateway).collect()
+}
+
+fn dns() -> Vec<IpAddr> {
+    let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+    let servers = nameservers(&read("/etc/resolv.conf"));
+    if servers.contains(&RESOLVED_STUB) {
+        let upstream = nameservers(&read("/run/systemd/resolve/resolv.conf"));
+        if !upstream.is_empty() {
+            return upstream;
+        }
+    }
+    servers
+}
+
+fn nameservers(resolv_conf: &str) -> Vec<IpAddr> {
+    resolv_conf
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        // Link-local IPv6 servers have the interface after a `%`
+        .filter_map(|server| server.trim().split('%').next()?.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gateway(addr: &str, interface: &str) -> Gateway {
+        Gateway {
+            addr: addr.parse().unwrap(),
+            interface: ArcStr::from(interface),
+        }
+    }
+
+    // Addresses are in the kernel's byte order
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parses_ipv4_routes() {
+        let routes = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+eth0\t00000000\tFE01A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+tun0\t00000000\t0100080A\t0002\t0\t0\t50\t00000000\t0\t0\t0
+";
+        // In order of metric, skipping the subnet route and the route that's down
+        assert_eq!(
+            ipv4_gateways(routes),
+            [
+                gateway("192.168.1.254", "eth0"),
+                gateway("192.168.1.1", "wlan0")
+            ]
+        );
+        assert!(ipv4_gateways("").is_empty());
+    }
+
+    #[test]
+    fn parses_ipv6_routes() {
+        let routes = "\
+20010db8000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     wlan0
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     wlan0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe80000000000000022186fffe123456 00000400 00000001 00000000 00450003     wlan0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+        assert_eq!(
+            ipv6_gateways(routes),
+            [gateway("fe80::221:86ff:fe12:3456", "wlan0")]
+        );
+    }
+
+    #[test]
+    fn parses_nameservers() {
+        let resolv_conf = "\
+# This is /run/systemd/resolve/stub-resolv.conf managed by man:systemd-resolved(8).
+nameserver 127.0.0.53
+ nameserver 2001:4860:4860::8888
+nameserver fe80::1%wlan0
+nameserver not-an-address
+options edns0 trust-ad
+search home.arpa
+";
+        assert_eq!(
+            nameservers(resolv_conf),
+            [
+                RESOLVED_STUB,
+                "2001:4860:4860::8888".parse().unwrap(),
+                "fe80::1".parse().unwrap()
+            ]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Temperature readings from hwmon drivers and thermal zones, in degrees Celsius
+//!
+//! See <https://docs.kernel.org/hwmon/sysfs-interface.html>
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glob::glob;
+
+/// hwmon drivers that report the CPU temperature, and the label of the sensor to use, in order of
+/// preference. Labels are matched by prefix, and the hottest match wins, so `Package id` covers
+/// every socket.
+const CPU_SENSORS: &[(&str, Option<&str>)] = &[
+    ("k10temp", Some("Tctl")),
+    ("k10temp", Some("Tdie")),
+    ("zenpower", Some("Tdie")),
+    ("coretemp", Some("Package id")),
+    ("cpu_thermal", None),
+    ("cpu-thermal", None),
+    ("soc_thermal", None),
+];
+
+/// Label of the main sensor on GPUs with several, e.g. amdgpu's `edge`, `junction` and `mem`
+const GPU_LABELS: &[&str] = &["edge"];
+
+/// One `temp<n>_input` file and its label, if it has one
+struct Reading {
+    label: Option<String>,
+    celsius: f64,
+}
+
+/// The CPU temperature, with sysfs mounted at `sysfs`
+pub fn cpu_in(sysfs: &Path) -> Option<f64> {
+    let chips = glob(&format!("{}/class/hwmon/hwmon*", sysfs.display()))
+        .ok()?
+        .flatten()
+        .filter_map(|hwmon| {
+            let name = fs::read_to_string(hwmon.join("name")).ok()?;
+            Some((name.trim().to_owned(), hwmon))
+        })
+        .collect::<Vec<_>>();
+    CPU_SENSORS
+        .iter()
+        .find_map(|(driver, label)| {
+            chips
+                .iter()
+                .filter(|(name, _)| name == driver)
+                .flat_map(|(_, hwmon)| readings(hwmon))
+                .filter(|reading| match (label, &reading.label) {
+                    (None, _) => true,
+                    (Some(label), Some(reading)) => reading.starts_with(label),
+                    (Some(_), None) => false,
+                })
+                .map(|reading| reading.celsius)
+                .max_by(f64::total_cmp)
+        })
+        .or_else(|| thermal_zone(sysfs))
+}
+
+/// The temperature of the GPU at `device`, e.g. `/sys/class/drm/card0/device`
+pub fn gpu(device: &Path) -> Option<f64> {
+    let readings = glob(&format!("{}/hwmon/hwmon*", device.display()))
+        .ok()?
+        .flatten()
+        .flat_map(|hwmon| readings(&hwmon))
+        .collect::<Vec<_>>();
+    readings
+        .iter()
+        .find(|reading| {
+            reading
+                .label
+                .as_deref()
+                .is_some_and(|label| GPU_LABELS.contains(&label))
+        })
+        .or_else(|| readings.first())
+        .map(|reading| reading.celsius)
+}
+
+/// Every temperature sensor of one hwmon device, in order
+fn readings(hwmon: &Path) -> Vec<Reading> {
+    let Ok(inputs) = glob(&format!("{}/temp*_input", hwmon.display())) else {
+        return Vec::new();
+    };
+    let mut inputs = inputs.flatten().collect::<Vec<PathBuf>>();
+    // `temp10_input` sorts before `temp2_input` otherwise
+    inputs.sort_by_key(|input| (input.as_os_str().len(), input.clone()));
+    inputs
+        .into_iter()
+        .filter_map(|input| {
+            let label = input.to_str()?.replace("_input", "_label");
+            Some(Reading {
+                label: fs::read_to_string(label)
+                    .ok()
+                    .map(|label| label.trim().to_owned()),
+                celsius: millidegrees(&input)?,
+            })
+        })
+        .collect()
+}
+
+/// Fall back to the kernel's thermal zones, which are all that some ARM boards have
+fn thermal_zone(sysfs: &Path) -> Option<f64> {
+    glob(&format!("{}/class/thermal/thermal_zone*", sysfs.display()))
+        .ok()?
+        .flatten()
+        .find_map(|zone| {
+            let kind = fs::read_to_string(zone.join("type")).ok()?;
+            let kind = kind.trim();
+            (kind == "x86_pkg_temp" || kind.contains("cpu") || kind.contains("soc"))
+                .then(|| millidegrees(&zone.join("temp")))?
+        })
+}
+
+fn millidegrees(path: &Path) -> Option<f64> {
+    let value = fs::read_to_string(path).ok()?.trim().parse::<i32>().ok()?;
+    Some(f64::from(value) / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    #[test]
+    fn k10temp_prefers_tctl() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/hwmon/hwmon0/name", "nvme\n")
+            .write("class/hwmon/hwmon0/temp1_input", "38850\n")
+            .write("class/hwmon/hwmon1/name", "k10temp\n")
+            .write("class/hwmon/hwmon1/temp1_input", "54125\n")
+            .write("class/hwmon/hwmon1/temp1_label", "Tctl\n")
+            .write("class/hwmon/hwmon1/temp3_input", "61000\n")
+            .write("class/hwmon/hwmon1/temp3_label", "Tccd1\n");
+        assert_eq!(cpu_in(sysfs.root()), Some(54.125));
+    }
+
+    #[test]
+    fn k10temp_falls_back_to_tdie() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/hwmon/hwmon2/name", "k10temp\n")
+            .write("class/hwmon/hwmon2/temp2_input", "47000\n")
+            .write("class/hwmon/hwmon2/temp2_label", "Tdie\n");
+        assert_eq!(cpu_in(sysfs.root()), Some(47.0));
+    }
+
+    #[test]
+    fn coretemp_uses_hottest_package() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/hwmon/hwmon3/name", "coretemp\n")
+            .write("class/hwmon/hwmon3/temp1_input", "56000\n")
+            .write("class/hwmon/hwmon3/temp1_label", "Package id 0\n")
+            .write("class/hwmon/hwmon3/temp2_input", "71000\n")
+            .write("class/hwmon/hwmon3/temp2_label", "Core 0\n")
+            .write("class/hwmon/hwmon4/name", "coretemp\n")
+            .write("class/hwmon/hwmon4/temp1_input", "58000\n")
+            .write("class/hwmon/hwmon4/temp1_label", "Package id 1\n");
+        assert_eq!(cpu_in(sysfs.root()), Some(58.0));
+    }
+
+    #[test]
+    fn amdgpu_prefers_edge() {
+        let device = FakeFs::new();
+        device
+            .write("hwmon/hwmon5/name", "amdgpu\n")
+            .write("hwmon/hwmon5/temp1_input", "65000\n")
+            .write("hwmon/hwmon5/temp1_label", "junction\n")
+            .write("hwmon/hwmon5/temp2_input", "52000\n")
+            .write("hwmon/hwmon5/temp2_label", "edge\n")
+            .write("hwmon/hwmon5/temp3_input", "70000\n")
+            .write("hwmon/hwmon5/temp3_label", "mem\n");
+        assert_eq!(gpu(device.root()), Some(52.0));
+    }
+
+    #[test]
+    fn gpu_without_labels_uses_first_sensor() {
+        let device = FakeFs::new();
+        device
+            .write("hwmon/hwmon6/temp2_input", "41000\n")
+            .write("hwmon/hwmon6/temp1_input", "40000\n");
+        assert_eq!(gpu(device.root()), Some(40.0));
+    }
+
+    #[test]
+    fn falls_back_to_thermal_zone() {
+        let sysfs = FakeFs::new();
+        sysfs
+            .write("class/hwmon/hwmon0/name", "acpitz\n")
+            .write("class/hwmon/hwmon0/temp1_input", "27800\n")
+            .write("class/thermal/thermal_zone0/type", "acpitz\n")
+            .write("class/thermal/thermal_zone0/temp", "27800\n")
+            .write("class/thermal/thermal_zone1/type", "cpu-thermal\n")
+            .write("class/thermal/thermal_zone1/temp", "48312\n");
+        assert_eq!(cpu_in(sysfs.root()), Some(48.312));
+    }
+
+    #[test]
+    fn no_sensors() {
+        let sysfs = FakeFs::new();
+        assert_eq!(cpu_in(sysfs.root()), None);
+    }
+
+    #[test]
+    fn converts_millidegrees() {
+        let sysfs = FakeFs::new();
+        sysfs.write("temp", "-5500\n").write("bad", "hot\n");
+        assert_eq!(millidegrees(&sysfs.join("temp")), Some(-5.5));
+        assert_eq!(millidegrees(&sysfs.join("bad")), None);
+        assert_eq!(millidegrees(&sysfs.join("missing")), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Shell detection, by walking up the process tree to the first interactive shell, then working
+//! out its version without running it if possible
+
+use std::{
+    env, os::unix::process::CommandExt, path::Path, process::Command, sync::OnceLock,
+    time::Duration,
+};
+
+use arcstr::ArcStr;
+use regex::bytes::Regex;
+
+use super::{
+    elf,
+    process::{self, Process, SHELLS},
+};
+use crate::info::Shell;
+
+/// Version strings embedded in shell executables, by shell
+const EMBEDDED_VERSIONS: &[(&str, &str)] = &[
+    ("bash", r"@\(#\)Bash version (\d+\.\d+\.\d+)"),
+    ("zsh", r"(?:zsh-|debian/)(\d+\.\d+(?:\.\d+)?)[-+]"),
+    ("mksh", r"@\(#\)MIRBSD KSH (R\d+\w*)"),
+    ("ksh", r"@\(#\)\$Id: Version \w+ ([\w+./-]+)"),
+];
+
+/// Options whose value is the next argument, rather than a script to run
+const OPERAND_OPTIONS: &[&str] = &[
+    "--rcfile",
+    "--init-file",
+    "--init-command",
+    "-o",
+    "+o",
+    "-O",
+    "+O",
+];
+
+/// Shells that understand `--version`
+const VERSION_FLAG: &[&str] = &["bash", "zsh", "fish", "tcsh", "nu", "xonsh", "pwsh"];
+
+/// How long to wait for `<shell> --version`
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+pub fn shell() -> Option<Shell> {
+    let Some(process) = process::ancestors().find(is_interactive_shell) else {
+        // Fall back to the user's login shell
+        let path = env::var("SHELL").ok()?;
+        let path = Path::new(&path);
+        let name = path.file_name()?.to_string_lossy();
+        return Some(Shell {
+            version: version(&name, path),
+            name: ArcStr::from(name),
+        });
+    };
+    let exe = process.exe();
+    let name = exe.as_deref().and_then(Path::file_name).map_or_else(
+        || process.name().to_owned(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let name = process::unwrapped(&name);
+    Some(Shell {
+        // Through `/proc`, which still reaches the running version once it's been upgraded
+        version: version(name, &process.exe_link()),
+        name: ArcStr::from(name),
+    })
+}
+
+/// Whether `process` is a shell that's waiting for commands, rather than running a script or a
+/// `-c` command on behalf of something else
+fn is_interactive_shell(process: &Process) -> bool {
+    SHELLS.iter().any(|shell| process.is(shell))
+        && is_interactive(process.cmdline().get(1..).unwrap_or_default())
+}
+
+/// Whether a shell given `args`, after `argv[0]`, reads commands from its terminal
+fn is_interactive(args: &[String]) -> bool {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-c" || !(arg.starts_with('-') || arg.starts_with('+')) {
+            return false;
+        }
+        if OPERAND_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+        }
+    }
+    true
+}
+
+/// Shells don't export their version variables, e.g. `$BASH_VERSION`, so they can't be read from
+/// `/proc/<pid>/environ`
+fn version(name: &str, exe: &Path) -> Option<ArcStr> {
+    embedded_version(name, exe)
+        .or_else(|| flag_version(name, exe))
+        .map(ArcStr::from)
+}
+
+fn embedded_regexes() -> &'static [(&'static str, Regex)] {
+    static EMBEDDED_REGEXES: OnceLock<Vec<(&str, Regex)>> = OnceLock::new();
+    EMBEDDED_REGEXES.get_or_init(|| {
+        EMBEDDED_VERSIONS
+            .iter()
+            .map(|(shell, pattern)| (*shell, Regex::new(pattern).unwrap()))
+            .collect()
+    })
+}
+
+fn flag_regex() -> &'static Regex {
+    static FLAG_REGEX: OnceLock<Regex> = OnceLock::new();
+    FLAG_REGEX.get_or_init(|| Regex::new(r"\d+\.\d+(?:\.\d+)?").unwrap())
+}
+
+fn embedded_version(name: &str, exe: &Path) -> Option<String> {
+    let (_, regex) = embedded_regexes()
+        .iter()
+        .find(|(shell, _)| *shell == name)?;
+    let rodata = elf::section(exe, ".rodata")?;
+    let version = regex.captures(&rodata)?.get(1)?;
+    Some(String::from_utf8_lossy(version.as_bytes()).into_owned())
+}
+
+/// Run `<shell> --version`, giving up if it takes too long
+fn flag_version(name: &str, exe: &Path) -> Option<String> {
+    if !VERSION_FLAG.contains(&name) {
+        return None;
+    }
+    let mut command = Command::new(exe);
+    // `exe` may be a link in `/proc`, which shouldn't end up in `argv[0]`
+    command.arg0(name).arg("--version");
+    let output = process::output(command, TIMEOUT)?;
+    let version = flag_regex().find(&output)?;
+    Some(String::from_utf8_lossy(version.as_bytes()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interactive(args: &[&str]) -> bool {
+        is_interactive(&args.iter().map(ToString::to_string).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn interactive_shells() {
+        assert!(interactive(&[]));
+        assert!(interactive(&["-l"]));
+        assert!(interactive(&["--login", "-i"]));
+        assert!(interactive(&["--rcfile", "foo"]));
+        assert!(interactive(&["--init-file", "/etc/bashrc", "-i"]));
+        assert!(interactive(&["-o", "vi"]));
+        assert!(interactive(&["+O", "histappend"]));
+    }
+
+    #[test]
+    fn finds_embedded_versions() {
+        let exe = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rodata64.o");
+        assert_eq!(embedded_version("bash", &exe).as_deref(), Some("5.2.15"));
+        assert_eq!(embedded_version("zsh", &exe), None);
+        assert_eq!(embedded_version("fish", &exe), None);
+    }
+
+    #[test]
+    fn scripts_and_commands() {
+        assert!(!interactive(&["-c", "echo hi"]));
+        assert!(!interactive(&["script.sh"]));
+        assert!(!interactive(&["-e", "script.sh"]));
+        assert!(!interactive(&["--rcfile", "foo", "script.sh"]));
+        assert!(!interactive(&["--", "script.sh"]));
+    }
+}
@@ -0,0 +1,256 @@
+//! Just enough of the `SQLite` file format to count the rows in a table, so package databases can
+//! be read without linking against `SQLite`
+//!
+//! See <https://www.sqlite.org/fileformat2.html>
+
+use std::{fs::File, os::unix::fs::FileExt, path::Path};
+
+const LEAF_TABLE: u8 = 0x0d;
+const INTERIOR_TABLE: u8 = 0x05;
+/// Guards against cycles in corrupt databases
+const MAX_DEPTH: u8 = 32;
+
+struct Database {
+    file: File,
+    page_size: usize,
+}
+
+impl Database {
+    fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        let mut header = [0u8; 100];
+        file.read_exact_at(&mut header, 0).ok()?;
+        if !header.starts_with(b"SQLite format 3\0") {
+            return None;
+        }
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+            1 => 65536,
+            size => usize::from(size),
+        };
+        Some(Self { file, page_size })
+    }
+
+    fn page(&self, number: u32) -> Option<Vec<u8>> {
+        let mut page = vec![0u8; self.page_size];
+        let offset = u64::from(number.checked_sub(1)?) * self.page_size as u64;
+        self.file.read_exact_at(&mut page, offset).ok()?;
+        Some(page)
+    }
+
+    /// Offsets of each cell in a b-tree page, along with the page type
+    fn cells(page: &[u8], number: u32) -> Option<(u8, Vec<usize>, Option<u32>)> {
+        // The first page starts with the database header
+        let header = if number == 1 { 100 } else { 0 };
+        let kind = *page.get(header)?;
+        let count = usize::from(u16::from_be_bytes([
+            *page.get(header + 3)?,
+            *page.get(header + 4)?,
+        ]));
+        let (rightmost, pointers) = if kind == INTERIOR_TABLE {
+            (
+                Some(u32::from_be_bytes(
+                    page.get(header + 8..header + 12)?.try_into().ok()?,
+                )),
+                header + 12,
+            )
+        } else {
+            (None, header + 8)
+        };
+        let cells = (0..count)
+            .map(|idx| {
+                let at = pointers + idx * 2;
+                Some(usize::from(u16::from_be_bytes([
+                    *page.get(at)?,
+                    *page.get(at + 1)?,
+                ])))
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        Some((kind, cells, rightmost))
+    }
+
+    fn count_rows(&self, root: u32, depth: u8) -> Option<u64> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let page = self.page(root)?;
+        match Self::cells(&page, root)? {
+            (LEAF_TABLE, cells, _) => Some(cells.len() as u64),
+            (INTERIOR_TABLE, cells, rightmost) => cells
+                .into_iter()
+                .map(|cell| {
+                    Some(u32::from_be_bytes(
+                        page.get(cell..cell + 4)?.try_into().ok()?,
+                    ))
+                })
+                .chain([rightmost])
+                .map(|child| self.count_rows(child?, depth + 1))
+                .sum(),
+            _ => None,
+        }
+    }
+
+    /// Find the root page of `table` by scanning the schema table on page 1
+    fn root_page(&self, table: &str) -> Option<u32> {
+        self.schema_rows(1, 0)?
+            .into_iter()
+            .find(|(kind, name, _)| kind == "table" && name == table)
+            .map(|(_, _, root)| root)
+    }
+
+    fn schema_rows(&self, number: u32, depth: u8) -> Option<Vec<(String, String, u32)>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let page = self.page(number)?;
+        match Self::cells(&page, number)? {
+            (LEAF_TABLE, cells, _) => Some(
+                cells
+                    .into_iter()
+                    .filter_map(|cell| schema_row(&page, cell))
+                    .collect(),
+            ),
+            (INTERIOR_TABLE, cells, rightmost) => {
+                let mut rows = Vec::new();
+                for child in cells
+                    .into_iter()
+                    .map(|cell| {
+                        Some(u32::from_be_bytes(
+                            page.get(cell..cell + 4)?.try_into().ok()?,
+                        ))
+                    })
+                    .chain([rightmost])
+                {
+                    rows.extend(self.schema_rows(child?, depth + 1)?);
+                }
+                Some(rows)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Read a variable-length integer, returning it and the number of bytes it took up
+fn varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut res = 0u64;
+    for (idx, byte) in bytes.iter().take(9).enumerate() {
+        if idx == 8 {
+            return Some(((res << 8) | u64::from(*byte), 9));
+        }
+        res = (res << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Some((res, idx + 1));
+        }
+    }
+    None
+}
+
+/// Size in bytes of a value with the given serial type
+const fn serial_size(serial: u64) -> u64 {
+    match serial {
+        1 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        5 => 6,
+        6 | 7 => 8,
+        n if n >= 12 => (n - 12) / 2,
+        _ => 0,
+    }
+}
+
+/// Parse the `type`, `name` and `rootpage` columns of a schema table cell
+fn schema_row(page: &[u8], cell: usize) -> Option<(String, String, u32)> {
+    let (_, len) = varint(page.get(cell..)?)?;
+    let (_, rowid_len) = varint(page.get(cell + len..)?)?;
+    let record = page.get(cell + len + rowid_len..)?;
+
+    let (header_size, mut at) = varint(record)?;
+    let header_size = usize::try_from(header_size).ok()?;
+    let mut serials = Vec::with_capacity(4);
+    while at < header_size && serials.len() < 4 {
+        let (serial, len) = varint(record.get(at..)?)?;
+        serials.push(serial);
+        at += len;
+    }
+
+    let mut values = Vec::with_capacity(4);
+    let mut offset = header_size;
+    for serial in serials {
+        let size = usize::try_from(serial_size(serial)).ok()?;
+        values.push((serial, record.get(offset..offset + size)?));
+        offset += size;
+    }
+    let text = |(serial, bytes): (u64, &[u8])| {
+        (serial >= 13 && serial % 2 == 1).then(|| String::from_utf8_lossy(bytes).into_owned())
+    };
+    let [kind, name, _, (serial, root)] = values.try_into().ok()?;
+    if !(1..=6).contains(&serial) {
+        return None;
+    }
+    let root = root
+        .iter()
+        .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+    Some((text(kind)?, text(name)?, u32::try_from(root).ok()?))
+}
+
+/// Count the rows in `table`, returning `None` if the file isn't an `SQLite` database or doesn't
+/// contain the table
+///
+/// Committed changes may still be waiting in the write-ahead log next to the database until the
+/// next checkpoint, which isn't read, so the count can be slightly out of date.
+pub fn count_rows(path: &Path, table: &str) -> Option<u64> {
+    let db = Database::open(path)?;
+    db.count_rows(db.root_page(table)?, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    /// An rpm database with 512-byte pages, so the schema table and `Packages` both span interior
+    /// and leaf pages, and every tenth package and one `CREATE TABLE` statement overflow
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpmdb.sqlite")
+    }
+
+    #[test]
+    fn counts_rows_across_pages() {
+        assert_eq!(count_rows(&fixture(), "Packages"), Some(120));
+        assert_eq!(count_rows(&fixture(), "Name"), Some(0));
+        assert_eq!(count_rows(&fixture(), "Missing"), None);
+    }
+
+    #[test]
+    fn finds_tables_with_overflowing_schema() {
+        let db = Database::open(&fixture()).unwrap();
+        assert!(db.root_page("Installtid").is_some());
+        assert_eq!(count_rows(&fixture(), "Installtid"), Some(0));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        assert_eq!(count_rows(&manifest, "Packages"), None);
+    }
+
+    #[test]
+    fn ignores_write_ahead_log() {
+        let dir = FakeFs::new();
+        dir.write("rpmdb.sqlite", fs::read(fixture()).unwrap())
+            .write("rpmdb.sqlite-wal", "");
+        assert_eq!(count_rows(&dir.join("rpmdb.sqlite"), "Packages"), Some(120));
+        dir.write("rpmdb.sqlite-wal", [0x37, 0x7f, 0x06, 0x82]);
+        assert_eq!(count_rows(&dir.join("rpmdb.sqlite"), "Packages"), Some(120));
+    }
+
+    #[test]
+    fn reads_varints() {
+        assert_eq!(varint(&[0x05]), Some((5, 1)));
+        assert_eq!(varint(&[0x81, 0x00]), Some((128, 2)));
+        assert_eq!(varint(&[0xff; 9]), Some((u64::MAX, 9)));
+        assert_eq!(varint(&[0x81]), None);
+    }
+}
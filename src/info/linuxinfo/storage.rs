@@ -0,0 +1,257 @@
+//! Physical drives, from the block devices in `/sys/block`
+
+use std::{fs, path::Path};
+
+use arcstr::ArcStr;
+use glob::glob;
+
+use crate::info::{Drive, DriveKind};
+
+/// Block devices that aren't physical drives: loop devices, RAM disks, device mapper and software
+/// RAID volumes, network block devices and floppies
+const VIRTUAL: &[&str] = &["loop", "ram", "zram", "dm-", "md", "nbd", "fd"];
+
+/// Transports, by a component of the device's path in sysfs
+const TRANSPORTS: &[(&str, &str)] = &[
+    ("/nvme/", "nvme"),
+    ("/usb", "usb"),
+    ("/ata", "sata"),
+    ("/mmc_host/", "mmc"),
+    ("/virtio", "virtio"),
+];
+
+/// The vendor SCSI reports for every SATA drive
+const ATA_VENDOR: &str = "ATA";
+
+pub fn storage() -> Vec<Drive> {
+    storage_in(Path::new("/sys"))
+}
+
+/// The drives, with sysfs mounted at `sysfs`
+fn storage_in(sysfs: &Path) -> Vec<Drive> {
+    let Ok(devices) = glob(&format!("{}/block/*", sysfs.display())) else {
+        return Vec::new();
+    };
+    devices
+        .flatten()
+        .filter_map(|device| {
+            let name = device.file_name()?.to_str()?.to_owned();
+            // eMMC chips also expose boot and replay-protected partitions as devices
+            if VIRTUAL.iter().any(|prefix| name.starts_with(prefix))
+                || name.contains("boot")
+                || name.ends_with("rpmb")
+            {
+                return None;
+            }
+            drive(&device, name)
+        })
+        .collect()
+}
+
+fn drive(device: &Path, name: String) -> Option<Drive> {
+    let read = |file: &str| {
+        fs::read_to_string(device.join(file))
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+    // Always in 512-byte sectors, whatever the drive's sector size
+    let size = read("size")?.parse::<u64>().ok()? * 512;
+    // Card readers and optical drives without media in them
+    if size == 0 {
+        return None;
+    }
+
+    let path = fs::canonicalize(device).ok()?;
+    let path = path.to_string_lossy();
+    let transport = TRANSPORTS
+        .iter()
+        .find(|(component, _)| path.contains(component))
+        .map(|(_, transport)| *transport);
+    let kind = if transport == Some("nvme") {
+        Some(DriveKind::Nvme)
+    } else {
+        match read("queue/rotational").as_deref() {
+            Some("1") => Some(DriveKind::Hdd),
+            Some("0") => Some(DriveKind::Ssd),
+            _ => None,
+        }
+    };
+
+    let model = read("device/model").or_else(|| read("device/name"));
+    // virtio reports its PCI vendor ID instead of a name
+    let vendor =
+        read("device/vendor").filter(|vendor| vendor != ATA_VENDOR && !vendor.starts_with("0x"));
+    let model = match (vendor, model) {
+        (Some(vendor), Some(model)) if !model.starts_with(&vendor) => {
+            Some(format!("{vendor} {model}"))
+        }
+        (_, model) => model,
+    };
+
+    Some(Drive {
+        name: ArcStr::from(name),
+        model: model.map(ArcStr::from),
+        size,
+        kind,
+        transport: transport.map(ArcStr::from),
+        removable: read("removable").as_deref() == Some("1"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::linuxinfo::fake_fs::FakeFs;
+
+    /// Add a block device at `path` under `devices`, linked from `block`
+    fn device(sysfs: &FakeFs, path: &str, files: &[(&str, &str)]) {
+        let name = path.rsplit('/').next().unwrap();
+        for (file, contents) in files {
+            sysfs.write(&format!("devices/{path}/{file}"), format!("{contents}\n"));
+        }
+        sysfs.symlink(&format!("devices/{path}"), &format!("block/{name}"));
+    }
+
+    fn drives(sysfs: &FakeFs) -> Vec<Drive> {
+        let mut drives = storage_in(sysfs.root());
+        drives.sort_by(|a, b| a.name.cmp(&b.name));
+        drives
+    }
+
+    #[test]
+    fn nvme() {
+        let sysfs = FakeFs::new();
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:01.1/0000:01:00.0/nvme/nvme0/nvme0n1",
+            &[
+                ("size", "1953525168"),
+                ("removable", "0"),
+                ("queue/rotational", "0"),
+                ("device/model", "Samsung SSD 980 PRO 1TB"),
+            ],
+        );
+        let drives = drives(&sysfs);
+        assert_eq!(drives.len(), 1);
+        let drive = &drives[0];
+        assert_eq!(drive.name, "nvme0n1");
+        assert_eq!(drive.model.as_deref(), Some("Samsung SSD 980 PRO 1TB"));
+        assert_eq!(drive.size, 1_953_525_168 * 512);
+        assert_eq!(drive.kind, Some(DriveKind::Nvme));
+        assert_eq!(drive.transport.as_deref(), Some("nvme"));
+        assert!(!drive.removable);
+    }
+
+    #[test]
+    fn sata_ssd_and_hdd() {
+        let sysfs = FakeFs::new();
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:17.0/ata1/host0/target0:0:0/0:0:0:0/block/sda",
+            &[
+                ("size", "976773168"),
+                ("removable", "0"),
+                ("queue/rotational", "0"),
+                ("device/vendor", "ATA"),
+                ("device/model", "CT500MX500SSD1"),
+            ],
+        );
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:17.0/ata2/host1/target1:0:0/1:0:0:0/block/sdb",
+            &[
+                ("size", "7814037168"),
+                ("removable", "0"),
+                ("queue/rotational", "1"),
+                ("device/vendor", "ATA"),
+                ("device/model", "ST4000DM004-2CV1"),
+            ],
+        );
+        let drives = drives(&sysfs);
+        assert_eq!(drives.len(), 2);
+        assert_eq!(drives[0].model.as_deref(), Some("CT500MX500SSD1"));
+        assert_eq!(drives[0].kind, Some(DriveKind::Ssd));
+        assert_eq!(drives[0].transport.as_deref(), Some("sata"));
+        assert_eq!(drives[1].kind, Some(DriveKind::Hdd));
+        assert_eq!(drives[1].transport.as_deref(), Some("sata"));
+    }
+
+    #[test]
+    fn usb_removable() {
+        let sysfs = FakeFs::new();
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:14.0/usb2/2-1/2-1:1.0/host2/target2:0:0/2:0:0:0/block/sdc",
+            &[
+                ("size", "60437492"),
+                ("removable", "1"),
+                ("queue/rotational", "1"),
+                ("device/vendor", "SanDisk"),
+                ("device/model", "Ultra"),
+            ],
+        );
+        let drives = drives(&sysfs);
+        assert_eq!(drives.len(), 1);
+        assert_eq!(drives[0].model.as_deref(), Some("SanDisk Ultra"));
+        assert_eq!(drives[0].transport.as_deref(), Some("usb"));
+        assert_eq!(drives[0].kind, Some(DriveKind::Hdd));
+        assert!(drives[0].removable);
+    }
+
+    #[test]
+    fn virtio_and_mmc_read_rotational() {
+        let sysfs = FakeFs::new();
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:04.0/virtio1/block/vda",
+            &[
+                ("size", "41943040"),
+                ("queue/rotational", "1"),
+                ("device/vendor", "0x1af4"),
+            ],
+        );
+        device(
+            &sysfs,
+            "platform/fe320000.mmc/mmc_host/mmc0/mmc0:aaaa/block/mmcblk0",
+            &[
+                ("size", "62333952"),
+                ("queue/rotational", "0"),
+                ("device/name", "SC64G"),
+            ],
+        );
+        let drives = drives(&sysfs);
+        assert_eq!(drives.len(), 2);
+        assert_eq!(drives[0].name, "mmcblk0");
+        assert_eq!(drives[0].model.as_deref(), Some("SC64G"));
+        assert_eq!(drives[0].transport.as_deref(), Some("mmc"));
+        assert_eq!(drives[0].kind, Some(DriveKind::Ssd));
+        assert_eq!(drives[1].name, "vda");
+        assert_eq!(drives[1].model, None);
+        assert_eq!(drives[1].transport.as_deref(), Some("virtio"));
+        assert_eq!(drives[1].kind, Some(DriveKind::Hdd));
+    }
+
+    #[test]
+    fn skips_virtual_and_empty_devices() {
+        let sysfs = FakeFs::new();
+        for name in ["loop0", "ram0", "zram0", "dm-0", "md127", "nbd0"] {
+            device(
+                &sysfs,
+                &format!("virtual/block/{name}"),
+                &[("size", "2097152"), ("queue/rotational", "0")],
+            );
+        }
+        device(
+            &sysfs,
+            "platform/fe320000.mmc/mmc_host/mmc0/mmc0:aaaa/block/mmcblk0/mmcblk0boot0",
+            &[("size", "8192")],
+        );
+        device(
+            &sysfs,
+            "pci0000:00/0000:00:17.0/ata3/host2/target2:0:0/2:0:0:0/block/sr0",
+            &[("size", "0"), ("removable", "1")],
+        );
+        assert!(drives(&sysfs).is_empty());
+    }
+}
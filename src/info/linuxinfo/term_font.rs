@@ -0,0 +1,220 @@
+//! Terminal font detection, by reading the config file of whichever terminal we're running in
+
+use std::{env, fs, path::PathBuf};
+
+use directories::BaseDirs;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{
+    dconf::Dconf,
+    fonts::{fontconfig, named, pango, qt, xlfd},
+    ini::Ini,
+};
+use crate::info::Font;
+
+/// The font used by `terminal`, given its display name
+pub fn font(terminal: &str) -> Option<Font> {
+    let dirs = BaseDirs::new()?;
+    match terminal {
+        "kitty" => kitty(&dirs),
+        "Alacritty" => alacritty(&dirs),
+        "foot" => foot(&dirs),
+        "WezTerm" => wezterm(&dirs),
+        "Konsole" | "Yakuake" => konsole(&dirs),
+        "GNOME Terminal" => gnome_terminal(),
+        "xterm" => xresources(&dirs, &["XTerm", "xterm"]),
+        "urxvt" | "rxvt" => xresources(&dirs, &["URxvt", "urxvt", "Rxvt", "rxvt"]),
+        _ => None,
+    }
+}
+
+/// The first of `paths` that exists
+fn first_existing(paths: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    paths.into_iter().find(|path| path.is_file())
+}
+
+fn kitty(dirs: &BaseDirs) -> Option<Font> {
+    let dir = env::var_os("KITTY_CONFIG_DIRECTORY")
+        .map_or_else(|| dirs.config_dir().join("kitty"), PathBuf::from);
+    let config = fs::read_to_string(dir.join("kitty.conf")).unwrap_or_default();
+    let setting = |name: &str| {
+        config.lines().rev().find_map(|line| {
+            let (key, value) = line.trim().split_once(char::is_whitespace)?;
+            (key == name).then(|| value.trim().to_owned())
+        })
+    };
+    let family = setting("font_family").unwrap_or_else(|| String::from("monospace"));
+    // Newer versions also accept `family="Fira Code" style=Retina`
+    let family = family
+        .strip_prefix("family=")
+        .map_or(family.as_str(), |family| {
+            family
+                .trim_start_matches('"')
+                .split('"')
+                .next()
+                .unwrap_or(family)
+        });
+    let size = setting("font_size").map_or(Some(11.0), |size| size.parse().ok());
+    named(family, size)
+}
+
+#[derive(Default, Deserialize)]
+struct AlacrittyConfig {
+    #[serde(default)]
+    font: AlacrittyFont,
+}
+
+#[derive(Default, Deserialize)]
+struct AlacrittyFont {
+    size: Option<f64>,
+    normal: Option<AlacrittyFace>,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyFace {
+    family: Option<String>,
+}
+
+fn alacritty(dirs: &BaseDirs) -> Option<Font> {
+    let config = dirs.config_dir();
+    let toml = first_existing([
+        config.join("alacritty/alacritty.toml"),
+        config.join("alacritty.toml"),
+        dirs.home_dir().join(".alacritty.toml"),
+    ]);
+    // Versions before 0.13 used YAML
+    let yaml = || {
+        first_existing([
+            config.join("alacritty/alacritty.yml"),
+            config.join("alacritty.yml"),
+            dirs.home_dir().join(".alacritty.yml"),
+        ])
+    };
+    let config = if let Some(path) = toml {
+        toml::from_str(&fs::read_to_string(path).ok()?).ok()?
+    } else if let Some(path) = yaml() {
+        serde_yaml::from_str(&fs::read_to_string(path).ok()?).ok()?
+    } else {
+        AlacrittyConfig::default()
+    };
+    let family = config.font.normal.and_then(|face| face.family);
+    named(
+        family.as_deref().unwrap_or("monospace"),
+        config.font.size.or(Some(11.25)),
+    )
+}
+
+fn foot(dirs: &BaseDirs) -> Option<Font> {
+    let config = Ini::read(dirs.config_dir().join("foot/foot.ini")).unwrap_or_default();
+    let pattern = config
+        .get("main", "font")
+        .or_else(|| config.get("", "font"))
+        .unwrap_or("monospace:size=8");
+    fontconfig(pattern).map(|font| Font {
+        size: font.size.or(Some(8.0)),
+        ..font
+    })
+}
+
+fn wezterm(dirs: &BaseDirs) -> Option<Font> {
+    let path = env::var_os("WEZTERM_CONFIG_FILE")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain([
+            dirs.config_dir().join("wezterm/wezterm.lua"),
+            dirs.home_dir().join(".wezterm.lua"),
+        ]);
+    let config = first_existing(path)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    // Matches `wezterm.font 'Name'`, `wezterm.font("Name")`, `wezterm.font { family = "Name" }`
+    // and `wezterm.font_with_fallback { "Name", ... }`
+    let family = Regex::new(
+        r#"font\s*=\s*wezterm\.font(?:_with_fallback)?\s*\(?\s*\{?\s*\{?\s*(?:family\s*=\s*)?["']([^"']+)["']"#,
+    )
+    .ok()?
+    .captures(&config)
+    .and_then(|captures| captures.get(1))
+    .map_or("JetBrains Mono", |family| family.as_str());
+    let size = Regex::new(r"font_size\s*=\s*([\d.]+)")
+        .ok()?
+        .captures(&config)
+        .and_then(|captures| captures.get(1)?.as_str().parse().ok())
+        .or(Some(12.0));
+    named(family, size)
+}
+
+fn konsole(dirs: &BaseDirs) -> Option<Font> {
+    let profile = Ini::read(dirs.config_dir().join("konsolerc"))
+        .and_then(|konsolerc| {
+            konsolerc
+                .get("Desktop Entry", "DefaultProfile")
+                .map(|profile| dirs.data_dir().join("konsole").join(profile))
+        })
+        .and_then(Ini::read);
+    profile
+        .as_ref()
+        .and_then(|profile| profile.get("Appearance", "Font"))
+        .and_then(qt)
+        // Otherwise Konsole uses the system's fixed width font
+        .or_else(|| {
+            let kdeglobals = Ini::read(dirs.config_dir().join("kdeglobals"))?;
+            kdeglobals.get("General", "fixed").and_then(qt)
+        })
+}
+
+fn gnome_terminal() -> Option<Font> {
+    const PROFILES: &str = "/org/gnome/terminal/legacy/profiles:/";
+    let dconf = Dconf::user()?;
+    let profile = dconf
+        .get(&format!("{PROFILES}default"))
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_else(|| String::from("b1dcc9dd-5262-4d8d-a863-c897e6d979b9"));
+    let key = |name: &str| format!("{PROFILES}:{profile}/{name}");
+    let use_system_font = dconf
+        .get(&key("use-system-font"))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+    if !use_system_font {
+        if let Some(font) = dconf
+            .get(&key("font"))
+            .and_then(|value| value.as_str().and_then(pango))
+        {
+            return Some(font);
+        }
+    }
+    dconf
+        .get("/org/gnome/desktop/interface/monospace-font-name")
+        .and_then(|value| value.as_str().and_then(pango))
+        .or_else(|| pango("Monospace 11"))
+}
+
+/// Look up X resources for any of `classes`, e.g. `XTerm*faceName: Hack`
+fn xresources(dirs: &BaseDirs, classes: &[&str]) -> Option<Font> {
+    let home = dirs.home_dir();
+    let resources = [".Xresources", ".Xdefaults"]
+        .iter()
+        .filter_map(|file| fs::read_to_string(home.join(file)).ok())
+        .collect::<String>();
+    let resource = |name: &str| {
+        resources.lines().rev().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let key = key.trim();
+            let class = key.strip_suffix(name)?.strip_suffix(['*', '.'])?;
+            (class.is_empty() || classes.contains(&class)).then(|| value.trim())
+        })
+    };
+    let face = resource("faceName").or_else(|| resource("font"))?;
+    let face = face.strip_prefix("xft:").unwrap_or(face);
+    if face.starts_with('-') {
+        return xlfd(face);
+    }
+    let font = fontconfig(face)?;
+    Some(Font {
+        size: font
+            .size
+            .or_else(|| resource("faceSize").and_then(|size| size.parse().ok())),
+        ..font
+    })
+}
@@ -0,0 +1,243 @@
+//! Terminal emulator detection, by walking up the process tree from our parent until we find a
+//! terminal, a multiplexer, or an SSH server
+
+use std::{env, path::PathBuf, process::Command, time::Duration};
+
+use arcstr::ArcStr;
+
+use super::process::{self, Process, SHELLS, WRAPPERS};
+use crate::info::Terminal;
+
+/// Process name and display name of known terminal emulators
+const TERMINALS: &[(&str, &str)] = &[
+    ("alacritty", "Alacritty"),
+    ("blackbox", "Black Box"),
+    ("code", "VS Code"),
+    ("vscode", "VS Code"),
+    ("contour", "Contour"),
+    ("cool-retro-term", "cool-retro-term"),
+    ("deepin-terminal", "Deepin Terminal"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("ghostty", "Ghostty"),
+    ("gnome-terminal-server", "GNOME Terminal"),
+    ("guake", "Guake"),
+    ("kgx", "GNOME Console"),
+    ("kitty", "kitty"),
+    ("konsole", "Konsole"),
+    ("lxterminal", "LXTerminal"),
+    ("mate-terminal", "MATE Terminal"),
+    ("ptyxis-agent", "Ptyxis"),
+    ("qterminal", "QTerminal"),
+    ("rio", "Rio"),
+    ("sakura", "Sakura"),
+    ("st", "st"),
+    ("terminator", "Terminator"),
+    ("terminology", "Terminology"),
+    ("tilda", "Tilda"),
+    ("tilix", "Tilix"),
+    ("urxvt", "urxvt"),
+    ("urxvtd", "urxvt"),
+    ("rxvt", "rxvt"),
+    ("warp", "Warp"),
+    ("wezterm-gui", "WezTerm"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("xterm", "xterm"),
+    ("yakuake", "Yakuake"),
+];
+
+/// How long to wait for tmux to answer
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Name, server process name and client process name of known terminal multiplexers
+const MULTIPLEXERS: &[(&str, &str, &str)] = &[
+    ("tmux", "tmux: server", "tmux: client"),
+    ("screen", "SCREEN", "screen"),
+    ("zellij", "zellij", "zellij"),
+];
+
+pub fn terminal() -> Option<Terminal> {
+    let mut res = Terminal {
+        name: None,
+        multiplexer: None,
+        ssh_client: None,
+    };
+    // The first process we don't recognise, in case it's a terminal we don't know about and the
+    // environment doesn't say
+    let mut unknown = None;
+    let mut processes = process::ancestors().collect::<Vec<_>>();
+    let mut idx = 0;
+    while let Some(process) = processes.get(idx) {
+        idx += 1;
+        if let Some(name) = known(process.name()) {
+            res.name = Some(name);
+            break;
+        }
+        if process.is("sshd") || process.is("sshd-session") {
+            res.ssh_client = ssh_client();
+            break;
+        }
+        if let Some((name, _, client)) = MULTIPLEXERS
+            .iter()
+            .find(|(_, server, _)| process.is(server))
+        {
+            res.multiplexer = Some(ArcStr::from(*name));
+            // The server is detached from the terminal, so carry on from its client
+            let Some(client) = attached_client(name, process, client) else {
+                break;
+            };
+            processes = client.lineage().skip(1).collect();
+            idx = 0;
+        } else if unknown.is_none() && !skipped(process) {
+            unknown = Some(ArcStr::from(process.name()));
+        }
+    }
+    if res.name.is_none() && res.ssh_client.is_none() {
+        res.name = fallback(unknown, |name| env::var(name).ok());
+    }
+    (res.name.is_some() || res.ssh_client.is_some()).then_some(res)
+}
+
+/// The client of `server` that's showing the session we're in, or `None` if that can't be told
+/// apart from its other clients, in which case the environment is asked instead
+fn attached_client(multiplexer: &str, server: &Process, client: &str) -> Option<Process> {
+    let uid = server.uid()?;
+    let mut clients = process::all()
+        .filter(|process| {
+            process.is(client) && process.pid != server.pid && process.uid() == Some(uid)
+        })
+        .collect::<Vec<_>>();
+    // Only tmux will say which terminal the session is being shown on
+    if multiplexer == "tmux" && clients.len() > 1 {
+        let tty = tmux_client_tty()?;
+        clients.retain(|client| client.tty().as_deref() == Some(tty.as_path()));
+    }
+    if clients.len() == 1 {
+        clients.pop()
+    } else {
+        None
+    }
+}
+
+/// The terminal of the client that last used the current tmux session, e.g. `/dev/pts/3`
+fn tmux_client_tty() -> Option<PathBuf> {
+    let mut command = Command::new("tmux");
+    command.args(["display-message", "-p", "#{client_tty}"]);
+    let output = process::output(command, TIMEOUT)?;
+    let tty = String::from_utf8(output).ok()?;
+    Some(PathBuf::from(tty.trim())).filter(|tty| tty.is_absolute())
+}
+
+fn ssh_client() -> Option<ArcStr> {
+    let connection = env::var("SSH_CONNECTION")
+        .or_else(|_| env::var("SSH_CLIENT"))
+        .ok()?;
+    connection.split_ascii_whitespace().next().map(ArcStr::from)
+}
+
+/// The display name of a known terminal, from either its (possibly truncated) process name or its
+/// display name
+fn known(name: &str) -> Option<ArcStr> {
+    TERMINALS
+        .iter()
+        .find(|(process, display)| {
+            process
+                .get(..15)
+                .unwrap_or(process)
+                .eq_ignore_ascii_case(name)
+                || display.eq_ignore_ascii_case(name)
+        })
+        .map(|(_, display)| ArcStr::from(*display))
+}
+
+/// Shells and wrappers, which run inside a terminal rather than being one
+fn skipped(process: &Process) -> bool {
+    SHELLS.iter().chain(WRAPPERS).any(|name| process.is(name))
+}
+
+/// Ask the environment, through `var`, for when the terminal isn't one of our ancestors or isn't
+/// known, and only then go with the first ancestor we didn't recognise
+fn fallback(unknown: Option<ArcStr>, var: impl Fn(&str) -> Option<String>) -> Option<ArcStr> {
+    // Multiplexers set `$TERM_PROGRAM` to themselves, which we've already covered
+    let var = |name| {
+        var(name).filter(|value: &String| {
+            !value.is_empty() && !MULTIPLEXERS.iter().any(|(mux, ..)| value == mux)
+        })
+    };
+    if let Some(term) = var("TERM_PROGRAM").or_else(|| var("TERMINAL")) {
+        return Some(known(&term).unwrap_or_else(|| ArcStr::from(term)));
+    }
+    let Some(term) = var("TERM") else {
+        return unknown;
+    };
+    // Some terminals set `$TERM` to their own terminfo entry, e.g. `xterm-kitty`
+    Some(
+        term.strip_prefix("xterm-")
+            .and_then(known)
+            .unwrap_or_else(|| ArcStr::from(term)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(comm: &str) -> Process {
+        Process {
+            pid: 4242,
+            ppid: 4241,
+            comm: comm.to_owned(),
+        }
+    }
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn skips_wrappers() {
+        for comm in [
+            "timeout",
+            "bash",
+            "sshd",
+            "tmux: client",
+            "SCREEN",
+            ".zsh-wrapped",
+        ] {
+            assert!(skipped(&process(comm)), "{comm}");
+        }
+        assert!(!skipped(&process("kitty")));
+        assert!(!skipped(&process("lazygit")));
+    }
+
+    #[test]
+    fn prefers_the_environment_to_unknown_ancestors() {
+        let lazygit = || Some(ArcStr::from("lazygit"));
+        assert_eq!(
+            fallback(lazygit(), env(&[("TERM", "xterm-kitty")])).as_deref(),
+            Some("kitty")
+        );
+        assert_eq!(
+            fallback(
+                lazygit(),
+                env(&[("TERM_PROGRAM", "WezTerm"), ("TERM", "xterm-256color")])
+            )
+            .as_deref(),
+            Some("WezTerm")
+        );
+        assert_eq!(
+            fallback(
+                lazygit(),
+                env(&[("TERM_PROGRAM", "tmux"), ("TERM", "tmux-256color")])
+            )
+            .as_deref(),
+            Some("tmux-256color")
+        );
+        assert_eq!(fallback(lazygit(), env(&[])).as_deref(), Some("lazygit"));
+        assert_eq!(fallback(None, env(&[("TERM", "")])), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Wireless link quality, from `/proc/net/wireless`, and the frequency from nl80211
+
+use std::fs;
+
+use arcstr::ArcStr;
+use glob::glob;
+
+use super::nl80211;
+use crate::info::Wifi;
+
+/// The best link quality cfg80211 reports, which it scales from the signal level
+const MAX_QUALITY: f64 = 70.0;
+
+pub fn wifi() -> Vec<Wifi> {
+    let Ok(interfaces) = glob("/sys/class/net/*/wireless") else {
+        return Vec::new();
+    };
+    let stats = fs::read_to_string("/proc/net/wireless").unwrap_or_default();
+    interfaces
+        .flatten()
+        .filter_map(|wireless| {
+            let sysfs = wireless.parent()?;
+            let name = sysfs.file_name()?.to_str()?;
+            // Only interfaces that are connected to a network
+            if !fs::read_to_string(sysfs.join("operstate")).is_ok_and(|state| state.trim() == "up")
+            {
+                return None;
+            }
+            let (quality, signal) = link(&stats, name);
+            // Interfaces that aren't driven by cfg80211 don't speak nl80211
+            let frequency = sysfs
+                .join("phy80211")
+                .exists()
+                .then(|| nl80211::frequency(name))
+                .flatten();
+            Some(Wifi {
+                interface: ArcStr::from(name),
+                quality,
+                signal,
+                frequency,
+            })
+        })
+        .collect()
+}
+
+/// The link quality, as a percentage, and the signal level, in dBm, of `interface` from the
+/// contents of `/proc/net/wireless`
+fn link(stats: &str, interface: &str) -> (Option<u8>, Option<i32>) {
+    // `face: status link level noise ...`, where the link quality and signal level have trailing
+    // dots
+    let fields = stats
+        .lines()
+        .skip(2)
+        .find_map(|line| line.trim_start().strip_prefix(interface)?.strip_prefix(':'))
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let field = |idx: usize| fields.get(idx)?.trim_end_matches('.').parse::<f64>().ok();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let quality = field(1).map(|link| (link / MAX_QUALITY * 100.0).round().min(100.0) as u8);
+    #[allow(clippy::cast_possible_truncation)]
+    let signal = field(2).map(|level| level as i32);
+    (quality, signal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: &str = "\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+wlp0s20f3: 0000   56.  -54.  -256        0      0      0      0     12        0
+  wlan1: 0000   70.  -38.  -256        0      0      0      0      0        0
+";
+
+    #[test]
+    fn reads_link_quality_and_signal() {
+        assert_eq!(link(STATS, "wlp0s20f3"), (Some(80), Some(-54)));
+        assert_eq!(link(STATS, "wlan1"), (Some(100), Some(-38)));
+    }
+
+    #[test]
+    fn skips_missing_interfaces() {
+        assert_eq!(link(STATS, "wlan"), (None, None));
+        assert_eq!(link(STATS, "face"), (None, None));
+        assert_eq!(link("", "wlan1"), (None, None));
+    }
+}
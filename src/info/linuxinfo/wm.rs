@@ -0,0 +1,141 @@
+//! Window manager detection, by asking the Wayland compositor who it is, or failing that, by
+//! looking for a known WM among the running processes
+
+use std::{
+    env, mem,
+    os::{fd::AsRawFd, unix::net::UnixStream},
+    path::Path,
+};
+
+use arcstr::ArcStr;
+
+use super::process::{self, unwrapped, Process};
+use crate::info::{Protocol, Wm};
+
+/// Process name, display name, and the protocol it speaks if it only supports one
+const KNOWN: &[(&str, &str, Option<Protocol>)] = &[
+    ("sway", "Sway", Some(Protocol::Wayland)),
+    ("Hyprland", "Hyprland", Some(Protocol::Wayland)),
+    ("river", "River", Some(Protocol::Wayland)),
+    ("wayfire", "Wayfire", Some(Protocol::Wayland)),
+    ("labwc", "labwc", Some(Protocol::Wayland)),
+    ("niri", "niri", Some(Protocol::Wayland)),
+    ("weston", "Weston", Some(Protocol::Wayland)),
+    ("dwl", "dwl", Some(Protocol::Wayland)),
+    ("hikari", "hikari", Some(Protocol::Wayland)),
+    ("cosmic-comp", "COSMIC", Some(Protocol::Wayland)),
+    ("kwin_wayland", "KWin", Some(Protocol::Wayland)),
+    ("kwin_x11", "KWin", Some(Protocol::X11)),
+    ("kwin", "KWin", None),
+    ("gnome-shell", "Mutter", None),
+    ("mutter", "Mutter", None),
+    ("cinnamon", "Muffin", None),
+    ("muffin", "Muffin", None),
+    ("gala", "Gala", None),
+    ("enlightenment", "Enlightenment", None),
+    ("qtile", "Qtile", None),
+    ("budgie-wm", "Budgie", Some(Protocol::X11)),
+    ("marco", "Marco", Some(Protocol::X11)),
+    ("metacity", "Metacity", Some(Protocol::X11)),
+    ("xfwm4", "Xfwm4", Some(Protocol::X11)),
+    ("openbox", "Openbox", Some(Protocol::X11)),
+    ("fluxbox", "Fluxbox", Some(Protocol::X11)),
+    ("icewm", "IceWM", Some(Protocol::X11)),
+    ("i3", "i3", Some(Protocol::X11)),
+    ("bspwm", "bspwm", Some(Protocol::X11)),
+    ("awesome", "awesome", Some(Protocol::X11)),
+    ("dwm", "dwm", Some(Protocol::X11)),
+    ("xmonad", "XMonad", Some(Protocol::X11)),
+    ("herbstluftwm", "herbstluftwm", Some(Protocol::X11)),
+    ("spectrwm", "spectrwm", Some(Protocol::X11)),
+    ("leftwm", "LeftWM", Some(Protocol::X11)),
+    ("fvwm", "FVWM", Some(Protocol::X11)),
+    ("fvwm3", "FVWM", Some(Protocol::X11)),
+    ("jwm", "JWM", Some(Protocol::X11)),
+    ("compiz", "Compiz", Some(Protocol::X11)),
+];
+
+pub fn wm() -> Option<Wm> {
+    wayland_compositor().or_else(running_wm)
+}
+
+/// Look up a process name, allowing for suffixes like xmonad's `xmonad-x86_64-linux`
+fn known(comm: &str) -> Option<&'static (&'static str, &'static str, Option<Protocol>)> {
+    let comm = unwrapped(comm);
+    KNOWN.iter().find(|(process, ..)| {
+        comm.strip_prefix(process)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    })
+}
+
+/// Whichever protocol the current session is using, for WMs that support both
+fn session_protocol() -> Option<Protocol> {
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Some(Protocol::Wayland);
+    }
+    if env::var_os("DISPLAY").is_some() {
+        return Some(Protocol::X11);
+    }
+    match env::var("XDG_SESSION_TYPE").ok()?.as_str() {
+        "wayland" => Some(Protocol::Wayland),
+        "x11" => Some(Protocol::X11),
+        _ => None,
+    }
+}
+
+/// Connect to the `$WAYLAND_DISPLAY` socket and see which process is listening on the other end
+fn wayland_compositor() -> Option<Wm> {
+    let display = env::var_os("WAYLAND_DISPLAY")?;
+    let display = Path::new(&display);
+    let socket = if display.is_absolute() {
+        display.to_owned()
+    } else {
+        Path::new(&env::var_os("XDG_RUNTIME_DIR")?).join(display)
+    };
+    let stream = UnixStream::connect(socket).ok()?;
+    let comm = Process::get(peer_pid(&stream)?)?.comm;
+    let name = known(&comm).map_or_else(
+        || ArcStr::from(unwrapped(&comm)),
+        |(_, name, _)| ArcStr::from(*name),
+    );
+    Some(Wm {
+        name,
+        protocol: Some(Protocol::Wayland),
+    })
+}
+
+fn peer_pid(stream: &UnixStream) -> Option<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` outlive the call, and `len` holds the size of `cred`, so the
+    // kernel won't write past it
+    let res = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            std::ptr::addr_of_mut!(cred).cast(),
+            std::ptr::addr_of_mut!(len),
+        )
+    };
+    u32::try_from(cred.pid)
+        .ok()
+        .filter(|pid| res == 0 && *pid > 0)
+}
+
+/// Scan `/proc` for a known WM or compositor of ours, rather than e.g. the greeter's gnome-shell
+fn running_wm() -> Option<Wm> {
+    // SAFETY: getuid can't fail and has no preconditions
+    let uid = unsafe { libc::getuid() };
+    process::all()
+        .find_map(|process| known(&process.comm).filter(|_| process.uid() == Some(uid)))
+        .map(|(_, name, protocol)| Wm {
+            name: ArcStr::from(*name),
+            protocol: protocol.or_else(session_protocol),
+        })
+}
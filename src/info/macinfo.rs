@@ -1,19 +1,22 @@
 #![cfg(target_os = "macos")]
-use super::OSInfo;
+use super::{
+    Battery, Cpu, Cursor, Disk, Font, Gpu, Interface, Memory, Monitor, OSInfo, Shell, Terminal, Wm,
+};
+use crate::config::{DiskConfig, NetworkConfig};
 use arcstr::ArcStr;
+use core::ffi::CStr;
 use libc::timespec;
 use platform_info::{PlatformInfo, PlatformInfoAPI, UNameAPI};
-use core::ffi::CStr;
 use rustc_hash::FxHashMap;
-use std::{alloc::Layout, env};
+use std::{alloc::Layout, env, time::Duration};
 pub struct MacInfo {
-    uts: PlatformInfo
+    uts: PlatformInfo,
 }
 
 impl MacInfo {
     pub fn new() -> Self {
         Self {
-            uts: PlatformInfo::new().unwrap()
+            uts: PlatformInfo::new().unwrap(),
         }
     }
 }
@@ -63,32 +66,32 @@ impl OSInfo for MacInfo {
         None
     }
 
-    fn cursor(&self) -> Option<ArcStr> {
+    fn cursor(&self) -> Option<Cursor> {
         None
     }
 
-    fn terminal(&self) -> Option<ArcStr> {
+    fn terminal(&self) -> Option<Terminal> {
         None
     }
 
-    fn term_font(&self) -> Option<ArcStr> {
+    fn term_font(&self) -> Option<Font> {
         None
     }
 
-    fn gpus(&self) -> Vec<ArcStr> {
+    fn gpus(&self) -> Vec<Gpu> {
         Vec::new()
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self, _config: &DiskConfig) -> Vec<Disk> {
         Vec::new()
     }
 
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -108,7 +111,7 @@ impl OSInfo for MacInfo {
         None
     }
 
-    fn displays(&self) -> Vec<ArcStr> {
+    fn displays(&self) -> Vec<Monitor> {
         Vec::new()
     }
 
@@ -120,7 +123,7 @@ impl OSInfo for MacInfo {
         Some(ArcStr::from(self.uts.release().to_string_lossy()))
     }
 
-    fn wm(&self) -> Option<ArcStr> {
+    fn wm(&self) -> Option<Wm> {
         None
     }
 
@@ -128,11 +131,11 @@ impl OSInfo for MacInfo {
         None
     }
 
-    fn shell(&self) -> Option<ArcStr> {
+    fn shell(&self) -> Option<Shell> {
         None
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         None
     }
 
@@ -151,26 +154,22 @@ impl OSInfo for MacInfo {
         arcstr::literal!("mac")
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
+    fn uptime(&self) -> Option<Duration> {
         unsafe {
             let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
             libc::clock_gettime(libc::CLOCK_UPTIME_RAW, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec)
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
+            Some(Duration::from_secs(
+                time.as_ref().unwrap().tv_sec.try_into().ok()?,
+                // + Duration::from_nanos(time.as_ref().unwrap().tv_nsec)
             ))
         }
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
+    fn ip(&self, _config: &NetworkConfig) -> Vec<Interface> {
         Vec::new()
     }
 
     fn hostname(&self) -> Option<ArcStr> {
         self.uts.nodename().to_str().map(ArcStr::from)
-
     }
 }
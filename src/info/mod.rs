@@ -1,10 +1,12 @@
-use std::{
-    str::FromStr,
-    sync::{mpsc::Sender, Arc},
-};
+use std::{sync::Mutex, time::Duration};
 
 use arcstr::ArcStr;
-use crossterm::style::{Color, Stylize};
+use clap::ValueEnum;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_with::{serde_as, DurationSeconds};
+
+use crate::config::{DiskConfig, NetworkConfig};
 
 #[cfg(target_os = "ios")]
 use crate::info::iosinfo::IosInfo as get_info;
@@ -17,33 +19,42 @@ use crate::info::wininfo::WindowsInfo as get_info;
 pub mod iosinfo;
 pub mod linuxinfo;
 pub mod macinfo;
+mod types;
 pub mod wininfo;
+
+pub use types::*;
 #[allow(clippy::module_name_repetitions)]
 pub trait OSInfo: Send + Sync {
     fn sys_font(&self) -> Option<ArcStr> {
         None
     }
 
-    fn cursor(&self) -> Option<ArcStr> {
+    fn cursor(&self) -> Option<Cursor> {
         None
     }
-    fn terminal(&self) -> Option<ArcStr> {
+    fn terminal(&self) -> Option<Terminal> {
         None
     }
-    fn term_font(&self) -> Option<ArcStr> {
+    fn term_font(&self) -> Option<Font> {
         None
     }
-    fn gpus(&self) -> Vec<ArcStr> {
+    fn gpus(&self) -> Vec<Gpu> {
         Vec::new()
     }
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn swap(&self) -> Vec<Swap> {
         Vec::new()
     }
-    fn battery(&self) -> Option<ArcStr> {
-        None
+    fn disks(&self, _config: &DiskConfig) -> Vec<Disk> {
+        Vec::new()
+    }
+    fn storage(&self) -> Vec<Drive> {
+        Vec::new()
+    }
+    fn battery(&self) -> Vec<Battery> {
+        Vec::new()
     }
     fn locale(&self) -> Option<ArcStr> {
         None
@@ -57,10 +68,22 @@ pub trait OSInfo: Send + Sync {
     fn os(&self) -> Option<ArcStr> {
         None
     }
+    fn packages(&self) -> Vec<PackageCount> {
+        Vec::new()
+    }
     fn id(&self) -> ArcStr;
-    fn uptime(&self) -> Option<ArcStr>;
-    fn ip(&self) -> Vec<ArcStr>;
-    fn displays(&self) -> Vec<ArcStr> {
+    fn uptime(&self) -> Option<Duration>;
+    fn boot_time(&self) -> Option<BootTime> {
+        None
+    }
+    fn ip(&self, config: &NetworkConfig) -> Vec<Interface>;
+    fn wifi(&self) -> Vec<Wifi> {
+        Vec::new()
+    }
+    fn routing(&self) -> Option<Routing> {
+        None
+    }
+    fn displays(&self) -> Vec<Monitor> {
         Vec::new()
     }
 
@@ -74,7 +97,7 @@ pub trait OSInfo: Send + Sync {
         None
     }
 
-    fn wm(&self) -> Option<ArcStr> {
+    fn wm(&self) -> Option<Wm> {
         None
     }
 
@@ -82,10 +105,10 @@ pub trait OSInfo: Send + Sync {
         None
     }
 
-    fn shell(&self) -> Option<ArcStr> {
+    fn shell(&self) -> Option<Shell> {
         None
     }
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         None
     }
 
@@ -94,180 +117,178 @@ pub trait OSInfo: Send + Sync {
     }
 }
 
-// #[derive(Debug)]
-// pub struct Info {
-//     pub os: Option<ArcStr>,
-//     pub machine: Option<ArcStr>,
-//     pub kernel: Option<ArcStr>,
-//     pub uptime: Option<ArcStr>,
-//     pub username: Option<ArcStr>,
-//     pub hostname: Option<ArcStr>,
-//     pub resolution: Vec<ArcStr>,
-//     pub wm: Option<ArcStr>,
-//     pub de: Option<ArcStr>,
-//     pub shell: Option<ArcStr>,
-//     pub cpu: Option<ArcStr>,
-//     pub font: Option<ArcStr>,
-//     pub cursor: Option<ArcStr>,
-//     pub terminal: Option<ArcStr>,
-//     pub terminal_font: Option<ArcStr>,
-//     pub gpus: Vec<ArcStr>,
-//     pub memory: Option<ArcStr>,
-//     pub disks: Vec<(ArcStr, ArcStr)>,
-//     pub battery: Option<ArcStr>,
-//     pub locale: Option<ArcStr>,
-//     pub theme: Option<ArcStr>,
-//     pub icons: Option<ArcStr>,
-//     pub ip: Vec<ArcStr>,
-//     pub id: ArcStr,
-// }
+/// Everything gathered about the system, with unrequested modules left empty
+#[serde_as]
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Info {
+    pub id: ArcStr,
+    pub os: Option<ArcStr>,
+    pub machine: Option<ArcStr>,
+    pub kernel: Option<ArcStr>,
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    pub uptime: Option<Duration>,
+    pub boot_time: Option<BootTime>,
+    pub username: Option<ArcStr>,
+    pub hostname: Option<ArcStr>,
+    pub packages: Vec<PackageCount>,
+    pub displays: Vec<Monitor>,
+    pub wm: Option<Wm>,
+    pub de: Option<ArcStr>,
+    pub shell: Option<Shell>,
+    pub cpu: Option<Cpu>,
+    pub font: Option<ArcStr>,
+    pub cursor: Option<Cursor>,
+    pub terminal: Option<Terminal>,
+    pub terminal_font: Option<Font>,
+    pub gpus: Vec<Gpu>,
+    pub memory: Option<Memory>,
+    pub swap: Vec<Swap>,
+    pub disks: Vec<Disk>,
+    pub storage: Vec<Drive>,
+    pub battery: Vec<Battery>,
+    pub locale: Option<ArcStr>,
+    pub theme: Option<ArcStr>,
+    pub icons: Option<ArcStr>,
+    pub ip: Vec<Interface>,
+    pub wifi: Vec<Wifi>,
+    pub routing: Option<Routing>,
+}
+
+/// A single line (or group of lines) in the info column
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, ValueEnum, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Module {
+    Title,
+    Os,
+    Machine,
+    Kernel,
+    Uptime,
+    BootTime,
+    Packages,
+    Shell,
+    Displays,
+    De,
+    Wm,
+    Theme,
+    Icons,
+    Font,
+    Cursor,
+    Terminal,
+    TerminalFont,
+    Cpu,
+    Gpu,
+    Memory,
+    Swap,
+    Disks,
+    Storage,
+    Battery,
+    Ip,
+    Wifi,
+    Routing,
+    Locale,
+    Hostname,
+    Username,
+    Palette,
+}
+
+impl Module {
+    /// Modules shown when the config does not specify any
+    pub const DEFAULT: &'static [Self] = &[
+        Self::Title,
+        Self::Os,
+        Self::Machine,
+        Self::Kernel,
+        Self::Uptime,
+        Self::Packages,
+        Self::Shell,
+        Self::Displays,
+        Self::De,
+        Self::Wm,
+        Self::Theme,
+        Self::Icons,
+        Self::Font,
+        Self::Cursor,
+        Self::Terminal,
+        Self::TerminalFont,
+        Self::Cpu,
+        Self::Gpu,
+        Self::Memory,
+        Self::Swap,
+        Self::Disks,
+        Self::Battery,
+        Self::Ip,
+        Self::Wifi,
+        Self::Locale,
+        Self::Palette,
+    ];
+}
 
 #[must_use]
 pub fn get_id() -> ArcStr {
     get_info::new().id()
 }
-#[allow(clippy::too_many_lines)]
-pub fn get_async(tx: &Sender<(ArcStr, ArcStr)>) {
-    let getter = Arc::new(get_info::new());
-    let username = getter.username().unwrap_or_default();
-    let hostname = getter.hostname().unwrap_or_default();
-    let y = arcstr::format!("{username}@{hostname}");
-    let repeats =
-        ArcStr::from_str(std::str::from_utf8(vec![b'-'; y.len()].as_slice()).unwrap()).unwrap();
-    tx.send((y, ArcStr::new())).unwrap();
-    tx.send((repeats, ArcStr::new())).unwrap();
-    rayon::scope(|s| {
-        // general_readout: general_readout.clone(),
-        s.spawn(|_| {
-            getter
-                .battery()
-                .and_then(|e| tx.send((arcstr::literal!("Battery"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .cpu()
-                .and_then(|e| tx.send((arcstr::literal!("cpu"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .cursor()
-                .and_then(|e| tx.send((arcstr::literal!("cursor"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .de()
-                .and_then(|e| tx.send((arcstr::literal!("de"), e)).ok());
-        });
-        s.spawn(|_| {
-            for e in getter.disks() {
-                tx.send(e).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .sys_font()
-                .and_then(|e| tx.send((arcstr::literal!("sys_font"), e)).ok());
-        });
-        s.spawn(|_| {
-            for (idx, e) in getter.gpus().into_iter().enumerate() {
-                tx.send((arcstr::format!("GPU {}", idx + 1), e)).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .hostname()
-                .and_then(|e| tx.send((arcstr::literal!("hostname"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .icons()
-                .and_then(|e| tx.send((arcstr::literal!("icons"), e)).ok());
-        });
-        s.spawn(|_| {
-            for e in getter.ip() {
-                tx.send((arcstr::literal!("IP"), e)).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .kernel()
-                .and_then(|e| tx.send((arcstr::literal!("kernel"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .locale()
-                .and_then(|e| tx.send((arcstr::literal!("locale"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .machine()
-                .and_then(|e| tx.send((arcstr::literal!("machine"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .memory()
-                .and_then(|e| tx.send((arcstr::literal!("memory"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .os()
-                .and_then(|e| tx.send((arcstr::literal!("OS"), e)).ok());
-        });
-        s.spawn(|_| {
-            for (idx, e) in getter.displays().into_iter().enumerate() {
-                tx.send((arcstr::format!("Display {}", idx + 1), e)).ok();
-            }
-        });
-        s.spawn(|_| {
-            getter
-                .shell()
-                .and_then(|e| tx.send((arcstr::literal!("Shell"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .terminal()
-                .and_then(|e| tx.send((arcstr::literal!("Terminal"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .term_font()
-                .and_then(|e| tx.send((arcstr::literal!("Term_font"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .theme()
-                .and_then(|e| tx.send((arcstr::literal!("Theme"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .uptime()
-                .and_then(|e| tx.send((arcstr::literal!("Uptime"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .username()
-                .and_then(|e| tx.send((arcstr::literal!("Username"), e)).ok());
-        });
-        s.spawn(|_| {
-            getter
-                .wm()
-                .and_then(|e| tx.send((arcstr::literal!("WM"), e)).ok());
-        });
+
+/// Gather the requested modules in parallel
+#[must_use]
+pub fn gather(modules: &[Module], disks: &DiskConfig, network: &NetworkConfig) -> Info {
+    let getter = get_info::new();
+    let info = Mutex::new(Info {
+        id: getter.id(),
+        ..Info::default()
     });
-    let (dark, light) = palette();
-    tx.send((ArcStr::new(), dark)).ok();
-    tx.send((ArcStr::new(), light)).ok();
+    modules
+        .par_iter()
+        .for_each(|module| probe(&getter, *module, disks, network, &info));
+    info.into_inner().unwrap()
 }
 
-fn palette() -> (ArcStr, ArcStr) {
-    (
-        (0..8u8)
-            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
-            .collect::<String>()
-            .into(),
-        (8..16u8)
-            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
-            .collect::<String>()
-            .into(),
-    )
+// The right-hand side of an assignment is evaluated before the left, so the lock is only held
+// while storing the result, not while probing
+fn probe(
+    getter: &impl OSInfo,
+    module: Module,
+    disks: &DiskConfig,
+    network: &NetworkConfig,
+    info: &Mutex<Info>,
+) {
+    let info = || info.lock().unwrap();
+    match module {
+        Module::Title => {
+            info().username = getter.username();
+            info().hostname = getter.hostname();
+        }
+        Module::Os => info().os = getter.os(),
+        Module::Machine => info().machine = getter.machine(),
+        Module::Kernel => info().kernel = getter.kernel(),
+        Module::Uptime => info().uptime = getter.uptime(),
+        Module::BootTime => info().boot_time = getter.boot_time(),
+        Module::Packages => info().packages = getter.packages(),
+        Module::Shell => info().shell = getter.shell(),
+        Module::Displays => info().displays = getter.displays(),
+        Module::De => info().de = getter.de(),
+        Module::Wm => info().wm = getter.wm(),
+        Module::Theme => info().theme = getter.theme(),
+        Module::Icons => info().icons = getter.icons(),
+        Module::Font => info().font = getter.sys_font(),
+        Module::Cursor => info().cursor = getter.cursor(),
+        Module::Terminal => info().terminal = getter.terminal(),
+        Module::TerminalFont => info().terminal_font = getter.term_font(),
+        Module::Cpu => info().cpu = getter.cpu(),
+        Module::Gpu => info().gpus = getter.gpus(),
+        Module::Memory => info().memory = getter.memory(),
+        Module::Swap => info().swap = getter.swap(),
+        Module::Disks => info().disks = getter.disks(disks),
+        Module::Storage => info().storage = getter.storage(),
+        Module::Battery => info().battery = getter.battery(),
+        Module::Ip => info().ip = getter.ip(network),
+        Module::Wifi => info().wifi = getter.wifi(),
+        Module::Routing => info().routing = getter.routing(),
+        Module::Locale => info().locale = getter.locale(),
+        Module::Hostname => info().hostname = getter.hostname(),
+        Module::Username => info().username = getter.username(),
+        Module::Palette => {}
+    }
 }
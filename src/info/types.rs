@@ -0,0 +1,255 @@
+use std::{net::IpAddr, time::Duration};
+
+use arcstr::ArcStr;
+use serde::Serialize;
+use serde_with::{serde_as, DurationSeconds};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cpu {
+    pub model: ArcStr,
+    /// Physical packages, each of which is an instance of `model`
+    pub sockets: Option<u32>,
+    /// Physical cores
+    pub cores: Option<u32>,
+    /// Logical processors
+    pub threads: Option<u32>,
+    pub freq_ghz: Option<f64>,
+    /// Degrees Celsius
+    pub temperature: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gpu {
+    pub vendor: Option<ArcStr>,
+    pub model: ArcStr,
+    /// The kernel driver bound to the device, e.g. `amdgpu`
+    pub driver: Option<ArcStr>,
+    pub kind: Option<GpuKind>,
+    /// Dedicated video memory, in bytes
+    pub vram: Option<Memory>,
+    /// Degrees Celsius
+    pub temperature: Option<f64>,
+}
+
+/// Whether a GPU is built into the CPU or `SoC`, or is a separate card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum GpuKind {
+    Integrated,
+    Discrete,
+}
+
+/// Memory usage, in bytes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Memory {
+    pub used: u64,
+    pub total: u64,
+    /// Kernel buffers for block devices
+    pub buffers: Option<u64>,
+    /// The page cache, which is freed when memory is needed
+    pub cached: Option<u64>,
+    /// `tmpfs` files and shared memory segments
+    pub shared: Option<u64>,
+    /// Memory reserved for huge pages, used or not
+    pub huge_pages: Option<u64>,
+}
+
+/// Swap usage, in bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Swap {
+    /// The zram device, e.g. `zram0`, or `None` for swap partitions and files, which are combined
+    pub zram: Option<ArcStr>,
+    pub used: u64,
+    pub total: u64,
+    /// For zram, the size of the data it holds before compression
+    pub original: Option<u64>,
+    /// For zram, the size of the data it holds after compression
+    pub compressed: Option<u64>,
+}
+
+/// Usage of a mounted filesystem, in bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Disk {
+    pub mount: ArcStr,
+    pub fs: Option<ArcStr>,
+    pub used: u64,
+    pub total: u64,
+}
+
+/// A network interface and the addresses it has
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Interface {
+    pub name: ArcStr,
+    pub kind: Option<InterfaceKind>,
+    /// IPv4 addresses first
+    pub addrs: Vec<IpNet>,
+    pub mac: Option<ArcStr>,
+    /// In Mbit/s
+    pub speed: Option<u32>,
+    /// `full` or `half`
+    pub duplex: Option<ArcStr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum InterfaceKind {
+    Ethernet,
+    Wifi,
+    Vpn,
+    Bridge,
+    /// Anything else that isn't backed by hardware, e.g. the ends of a container's `veth` pair
+    Virtual,
+}
+
+/// An address and the length of its network prefix, e.g. `192.168.1.20/24`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IpNet {
+    pub addr: IpAddr,
+    pub prefix_len: u8,
+}
+
+/// A wireless interface that's connected to a network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Wifi {
+    pub interface: ArcStr,
+    /// As a percentage
+    pub quality: Option<u8>,
+    /// In dBm
+    pub signal: Option<i32>,
+    /// In MHz
+    pub frequency: Option<u32>,
+}
+
+/// Where traffic leaves the computer and how names are resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Routing {
+    /// Default routes, IPv4 first and then by preference
+    pub gateways: Vec<Gateway>,
+    pub dns: Vec<IpAddr>,
+    /// Names of the VPN interfaces that are up
+    pub vpns: Vec<ArcStr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Gateway {
+    pub addr: IpAddr,
+    /// The interface the route goes out of
+    pub interface: ArcStr,
+}
+
+/// A physical drive, whether or not anything on it is mounted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drive {
+    /// The kernel's name for it, e.g. `nvme0n1` or `sda`
+    pub name: ArcStr,
+    pub model: Option<ArcStr>,
+    /// In bytes
+    pub size: u64,
+    pub kind: Option<DriveKind>,
+    /// How it's connected, e.g. `usb`, `sata` or `nvme`
+    pub transport: Option<ArcStr>,
+    pub removable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DriveKind {
+    Hdd,
+    Ssd,
+    Nvme,
+}
+
+/// The display server protocol a window manager speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Protocol {
+    X11,
+    Wayland,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Wm {
+    pub name: ArcStr,
+    pub protocol: Option<Protocol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cursor {
+    pub theme: ArcStr,
+    /// In pixels
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Font {
+    pub name: ArcStr,
+    /// In points
+    pub size: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Shell {
+    pub name: ArcStr,
+    pub version: Option<ArcStr>,
+}
+
+/// The terminal we're running in, or where we're being accessed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Terminal {
+    /// The terminal emulator, if it could be found
+    pub name: Option<ArcStr>,
+    /// e.g. tmux or screen
+    pub multiplexer: Option<ArcStr>,
+    /// The address of the SSH client, when running over SSH
+    pub ssh_client: Option<ArcStr>,
+}
+
+/// When the system booted, in the system's timezone at the time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BootTime {
+    /// Seconds since the Unix epoch
+    pub timestamp: i64,
+    /// Seconds ahead of UTC
+    pub utc_offset: i32,
+    /// The timezone's abbreviation, e.g. `CEST`
+    pub timezone: Option<ArcStr>,
+}
+
+/// Number of packages installed through a package manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PackageCount {
+    pub manager: &'static str,
+    pub count: u64,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Battery {
+    pub name: ArcStr,
+    /// The manufacturer and model, mostly reported by peripherals, e.g. `Logitech MX Master 3`
+    pub model: Option<ArcStr>,
+    pub percent: Option<u8>,
+    pub status: Option<ArcStr>,
+    /// How much it holds when full, as a percentage of what it held when new
+    pub health: Option<f64>,
+    /// In watts, while charging or discharging
+    pub power: Option<f64>,
+    /// Until it's empty while discharging, or until it's full while charging
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    pub time_remaining: Option<Duration>,
+    /// Whether the computer is plugged in, for batteries that power it
+    pub ac_online: Option<bool>,
+    /// Whether it powers a peripheral, e.g. a mouse, rather than the computer
+    pub peripheral: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Monitor {
+    /// The manufacturer and model, e.g. `DELL U2720Q`
+    pub name: Option<ArcStr>,
+    /// The output it's plugged into, e.g. `DP-1`
+    pub connector: Option<ArcStr>,
+    pub width: u32,
+    pub height: u32,
+    pub refresh_hz: Option<f64>,
+    /// The highest refresh rate the monitor supports
+    pub max_refresh_hz: Option<u32>,
+    /// In inches
+    pub diagonal: Option<f64>,
+}
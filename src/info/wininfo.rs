@@ -7,7 +7,8 @@ use itertools::Itertools;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use rustc_hash::FxHashMap;
 use std::mem::size_of;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
 use windows::Win32::System::SystemInformation::{
     RelationProcessorCore, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
 };
@@ -15,10 +16,15 @@ use windows::Win32::{
     Foundation::ERROR_BUFFER_OVERFLOW,
     NetworkManagement::IpHelper::{
         GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
-        GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+        GAA_FLAG_SKIP_MULTICAST, IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_PPP,
+        IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_TUNNEL, IP_ADAPTER_ADDRESSES_LH,
     },
+    NetworkManagement::Ndis::IfOperStatusUp,
     Networking::WinSock::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCKADDR_IN6},
-    System::{Power::GetSystemPowerStatus, SystemInformation::{GetLogicalProcessorInformationEx, RelationAll}},
+    System::{
+        Power::GetSystemPowerStatus,
+        SystemInformation::{GetLogicalProcessorInformationEx, RelationAll},
+    },
 };
 use winsafe::co::{SPI, SPIF};
 use winsafe::{
@@ -35,8 +41,11 @@ use winreg::{
     RegKey,
 };
 
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
+use crate::config::{DiskConfig, NetworkConfig};
+use crate::info::{
+    Battery, Cpu, Cursor, Disk, Font, Gpu, Interface, InterfaceKind, IpNet, Memory, Monitor,
+    OSInfo, Shell, Terminal, Wm,
+};
 
 #[derive(Default)]
 pub struct WindowsInfo {
@@ -55,7 +64,7 @@ impl WindowsInfo {
     }
 }
 impl OSInfo for WindowsInfo {
-    fn displays(&self) -> Vec<ArcStr> {
+    fn displays(&self) -> Vec<Monitor> {
         use std::sync::Mutex;
 
         use winsafe::{
@@ -66,11 +75,15 @@ impl OSInfo for WindowsInfo {
         let handle = HDC::NULL;
         handle
             .EnumDisplayMonitors(None, |_monitor, _hdc, rect| -> bool {
-                displays.lock().unwrap().push(arcstr::format!(
-                    "{}x{}",
-                    rect.right - rect.left,
-                    rect.bottom - rect.top
-                ));
+                displays.lock().unwrap().push(Monitor {
+                    width: u32::try_from(rect.right - rect.left).unwrap_or_default(),
+                    height: u32::try_from(rect.bottom - rect.top).unwrap_or_default(),
+                    name: None,
+                    connector: None,
+                    refresh_hz: None,
+                    max_refresh_hz: None,
+                    diagonal: None,
+                });
                 true
             })
             .ok();
@@ -152,11 +165,16 @@ impl OSInfo for WindowsInfo {
         ))
     }
 
-    fn wm(&self) -> Option<ArcStr> {
-        if winsafe::DwmIsCompositionEnabled().ok()? {
-            return Some(arcstr::literal!("Desktop Window Manager"));
-        }
-        Some(arcstr::literal!("Internal"))
+    fn wm(&self) -> Option<Wm> {
+        let name = if winsafe::DwmIsCompositionEnabled().ok()? {
+            arcstr::literal!("Desktop Window Manager")
+        } else {
+            arcstr::literal!("Internal")
+        };
+        Some(Wm {
+            name,
+            protocol: None,
+        })
     }
 
     fn de(&self) -> Option<ArcStr> {
@@ -181,8 +199,8 @@ impl OSInfo for WindowsInfo {
         None
     }
 
-    fn gpus(&self) -> Vec<ArcStr> {
-        || -> Option<Vec<ArcStr>> {
+    fn gpus(&self) -> Vec<Gpu> {
+        || -> Option<Vec<Gpu>> {
             let video: RegKey = self
                 .get_hklm()
                 .open_subkey(r"SYSTEM\CurrentControlSet\Control\Video\")
@@ -195,12 +213,19 @@ impl OSInfo for WindowsInfo {
                         video
                             .open_subkey(x.ok()?)
                             .map(|uuid| {
-                                uuid.enum_keys().find_map(|y| -> Option<ArcStr> {
+                                uuid.enum_keys().find_map(|y| -> Option<Gpu> {
                                     uuid.open_subkey(y.unwrap())
                                         .ok()?
                                         .get_value::<String, &str>("DriverDesc")
                                         .ok()
-                                        .map(ArcStr::from)
+                                        .map(|model| Gpu {
+                                            vendor: None,
+                                            model: ArcStr::from(model),
+                                            driver: None,
+                                            kind: None,
+                                            vram: None,
+                                            temperature: None,
+                                        })
                                 })
                                 // .collect::<ArcStr>()
                             })
@@ -233,95 +258,94 @@ impl OSInfo for WindowsInfo {
         .unwrap()
     }
 
-    fn uptime(&self) -> Option<ArcStr> {
-        let uptime = time::Duration::milliseconds(GetTickCount64().try_into().ok()?).to_string();
-        Some(
-            uptime
-                .split_inclusive('m')
-                .next()?
-                .replace('h', " hours, ")
-                .replace('m', " mins")
-                .into(),
-        )
+    fn uptime(&self) -> Option<Duration> {
+        Some(Duration::from_millis(GetTickCount64()))
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
+    fn ip(&self, config: &NetworkConfig) -> Vec<Interface> {
         unsafe {
-            let size = Box::into_raw(Box::new(0x3FFF));
-            let mut buf = Vec::<u8>::with_capacity(*size as usize);
-            let addrs: *mut IP_ADAPTER_ADDRESSES_LH = buf.as_mut_ptr().cast();
-            while GetAdaptersAddresses(
-                u32::from(AF_UNSPEC.0),
-                GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER,
-                None,
-                Some(addrs),
-                size,
-            ) == ERROR_BUFFER_OVERFLOW.0
-            {
-                buf.reserve_exact(*size as usize);
+            let mut size = 0x3FFF_u32;
+            // `u64`s so the adapters are aligned
+            let mut buf = Vec::<u64>::new();
+            loop {
+                buf.resize(size as usize / 8 + 1, 0);
+                match GetAdaptersAddresses(
+                    u32::from(AF_UNSPEC.0),
+                    GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER,
+                    None,
+                    Some(buf.as_mut_ptr().cast()),
+                    &mut size,
+                ) {
+                    0 => break,
+                    err if err == ERROR_BUFFER_OVERFLOW.0 => continue,
+                    _ => return Vec::new(),
+                }
             }
-            let mut ipv4_addrs: Vec<Ipv4Addr> = Vec::new();
-            let mut ipv6_addrs: Vec<Ipv6Addr> = Vec::new();
-            let mut adapter_current = addrs.as_ref();
             let mut res = Vec::new();
+            let mut adapter_current = buf.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>().as_ref();
             while let Some(adapter) = adapter_current {
-                let mut addr_current = (adapter).FirstUnicastAddress.as_ref();
+                adapter_current = adapter.Next.as_ref();
+                if adapter.OperStatus != IfOperStatusUp
+                    || adapter.IfType == IF_TYPE_SOFTWARE_LOOPBACK
+                {
+                    continue;
+                }
+                let kind = match adapter.IfType {
+                    IF_TYPE_ETHERNET_CSMACD => InterfaceKind::Ethernet,
+                    IF_TYPE_IEEE80211 => InterfaceKind::Wifi,
+                    IF_TYPE_TUNNEL | IF_TYPE_PPP => InterfaceKind::Vpn,
+                    _ => InterfaceKind::Virtual,
+                };
+                if kind == InterfaceKind::Virtual && !config.show_virtual {
+                    continue;
+                }
+                let mut addrs = Vec::new();
+                let mut addr_current = adapter.FirstUnicastAddress.as_ref();
                 while let Some(addr) = addr_current {
-                    match (*addr.Address.lpSockaddr).sa_family {
-                        AF_INET6 => {
-                            let ip_addr = Ipv6Addr::from(
-                                (*addr.Address.lpSockaddr.cast::<SOCKADDR_IN6>())
-                                    .sin6_addr
-                                    .u
-                                    .Byte,
-                            );
-                            if !ip_addr.is_loopback() && (ip_addr.segments()[0] & 0xfe00 == 0xfc00)
-                            {
-                                ipv6_addrs.push(ip_addr);
-                            }
-                        }
+                    addr_current = addr.Next.as_ref();
+                    let ip_addr = match (*addr.Address.lpSockaddr).sa_family {
                         AF_INET => {
                             #[allow(clippy::cast_ptr_alignment)]
-                            let ip_addr = Ipv4Addr::from(
-                                (*addr.Address.lpSockaddr.cast::<SOCKADDR_IN>())
-                                    .sin_addr
-                                    .S_un
-                                    .S_addr
-                                    .swap_bytes(),
-                            );
-                            if !ip_addr.is_loopback()
-                                && (ip_addr.is_private()/*|| ip_addr.is_link_local()*/)
-                            {
-                                ipv4_addrs.push(ip_addr);
-                            }
+                            let ip_addr = (*addr.Address.lpSockaddr.cast::<SOCKADDR_IN>())
+                                .sin_addr
+                                .S_un
+                                .S_addr;
+                            IpAddr::from(Ipv4Addr::from(u32::from_be(ip_addr)))
                         }
-                        _ => panic!(),
+                        AF_INET6 if config.show_ipv6 => {
+                            #[allow(clippy::cast_ptr_alignment)]
+                            let ip_addr = (*addr.Address.lpSockaddr.cast::<SOCKADDR_IN6>())
+                                .sin6_addr
+                                .u
+                                .Byte;
+                            IpAddr::from(Ipv6Addr::from(ip_addr))
+                        }
+                        _ => continue,
                     };
-                    res.push(());
-                    addr_current = addr.Next.as_ref();
+                    addrs.push(IpNet {
+                        addr: ip_addr,
+                        prefix_len: addr.OnLinkPrefixLength,
+                    });
                 }
-                adapter_current = adapter.Next.as_ref();
-            }
-            let mut res = Vec::with_capacity(2);
-            if !ipv4_addrs.is_empty() {
-                res.push(ArcStr::from(
-                    ipv4_addrs
-                        .par_iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                ));
-            }
-            if !ipv6_addrs.is_empty() {
-                res.push(ArcStr::from(
-                    ipv6_addrs
-                        .par_iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                ));
+                if addrs.is_empty() {
+                    continue;
+                }
+                addrs.sort_by_key(|addr| addr.addr.is_ipv6());
+                let mac = &adapter.PhysicalAddress[..adapter.PhysicalAddressLength.min(8) as usize];
+                res.push(Interface {
+                    name: ArcStr::from(adapter.FriendlyName.to_string().unwrap_or_default()),
+                    kind: Some(kind),
+                    addrs,
+                    mac: (!mac.is_empty()).then(|| {
+                        ArcStr::from(mac.iter().map(|byte| format!("{byte:02x}")).join(":"))
+                    }),
+                    // In bits per second, or `u64::MAX` if it isn't known
+                    speed: Some(adapter.TransmitLinkSpeed)
+                        .filter(|speed| *speed != u64::MAX)
+                        .and_then(|speed| u32::try_from(speed / 1_000_000).ok()),
+                    duplex: None,
+                });
             }
-
             res
         }
     }
@@ -348,76 +372,102 @@ impl OSInfo for WindowsInfo {
         Some(metrics.lfMenuFont.lfFaceName().into())
     }
 
-    fn cursor(&self) -> Option<ArcStr> {
+    fn cursor(&self) -> Option<Cursor> {
         None
     }
 
-    fn terminal(&self) -> Option<ArcStr> {
+    fn terminal(&self) -> Option<Terminal> {
         None
     }
 
-    fn term_font(&self) -> Option<ArcStr> {
+    fn term_font(&self) -> Option<Font> {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         let result = GlobalMemoryStatusEx().ok()?;
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format(result.ullTotalPhys - result.ullAvailPhys, 2),
-            bytecount_format(result.ullTotalPhys, 2),
-        ))
+        Some(Memory {
+            used: result.ullTotalPhys - result.ullAvailPhys,
+            total: result.ullTotalPhys,
+            ..Memory::default()
+        })
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self, config: &DiskConfig) -> Vec<Disk> {
         let q = GetLogicalDriveStrings();
         q.map_or(Vec::new(), |c| {
             c.par_iter()
+                .filter(|x| config.shows(x, x, false, || false))
                 .filter_map(|x| {
-                    Some((ArcStr::from(x), {
-                        let var_name = 0xDEAD;
-                        let mut total: Option<u64> = Some(var_name);
-                        let var_name = 0xDEAD;
-                        let mut free: Option<u64> = Some(var_name);
-                        GetDiskFreeSpaceEx(Some(x), None, total.as_mut(), free.as_mut()).ok()?;
-                        arcstr::format!(
-                            "{} / {}",
-                            bytecount_format(total? - free?, 0),
-                            bytecount_format(total?, 0)
-                        )
-                    }))
+                    let var_name = 0xDEAD;
+                    let mut total: Option<u64> = Some(var_name);
+                    let var_name = 0xDEAD;
+                    let mut free: Option<u64> = Some(var_name);
+                    GetDiskFreeSpaceEx(Some(x), None, total.as_mut(), free.as_mut()).ok()?;
+                    Some(Disk {
+                        mount: ArcStr::from(x),
+                        fs: None,
+                        used: total? - free?,
+                        total: total?,
+                    })
                 })
                 .collect()
         })
     }
 
-    fn battery(&self) -> Option<ArcStr> {
+    fn battery(&self) -> Vec<Battery> {
         unsafe {
             let mut system_power_status = Default::default();
-            GetSystemPowerStatus(&mut system_power_status).ok()?;
+            if GetSystemPowerStatus(&mut system_power_status).is_err() {
+                return Vec::new();
+            }
             let no_battery = system_power_status.BatteryFlag & 128 == 128;
             if no_battery {
-                return None;
+                return Vec::new();
             }
 
-            let ac_line_status = match system_power_status.ACLineStatus {
-                 0 => Some("unplugged"),
-                 1 => Some("plugged in"),
-                 255 => None,
-                 _ => None
+            let ac_online = match system_power_status.ACLineStatus {
+                0 => Some(false),
+                1 => Some(true),
+                _ => None,
+            };
+            // In seconds, or `u32::MAX` if it isn't known, e.g. while charging
+            let time_remaining = Some(system_power_status.BatteryLifeTime)
+                .filter(|seconds| *seconds != u32::MAX)
+                .map(|seconds| Duration::from_secs(u64::from(seconds)));
+            let charging = if system_power_status.BatteryFlag & 8 == 8 {
+                "charging"
+            } else {
+                "not charging"
             };
-            let charging = if system_power_status.BatteryFlag & 8 == 8 { "charging" } else { "not charging" };
-            let charge = match system_power_status.BatteryLifePercent { x if x <= 100 => Some(x.to_string()), _ => None };
-            let energy_saver = match system_power_status.SystemStatusFlag { 
+            let charge = match system_power_status.BatteryLifePercent {
+                x if x <= 100 => Some(x),
+                _ => None,
+            };
+            let energy_saver = match system_power_status.SystemStatusFlag {
                 0 => None,
                 1 => Some("energy saver on"),
-                x => panic!("unexpected value {} for SYSTEM_POWER_STATUS.SystemStatusFlag", x)
+                x => panic!(
+                    "unexpected value {} for SYSTEM_POWER_STATUS.SystemStatusFlag",
+                    x
+                ),
             };
-            let mut status_parts = Vec::with_capacity(3);
-            if ac_line_status.is_some() { status_parts.push(ac_line_status.unwrap()); }
+            let mut status_parts = Vec::with_capacity(2);
             status_parts.push(charging);
-            if energy_saver.is_some() { status_parts.push(energy_saver.unwrap()); }
-            Some(ArcStr::from(format!("{}% ({})", charge.unwrap_or("\u{221e}".to_string()), status_parts.join(", "))))
+            if energy_saver.is_some() {
+                status_parts.push(energy_saver.unwrap());
+            }
+            vec![Battery {
+                name: arcstr::literal!("Battery"),
+                model: None,
+                percent: charge,
+                status: Some(ArcStr::from(status_parts.join(", "))),
+                health: None,
+                power: None,
+                time_remaining,
+                ac_online,
+                peripheral: false,
+            }]
         }
     }
 
@@ -443,7 +493,7 @@ impl OSInfo for WindowsInfo {
         binding.first()?.values().next().map(ArcStr::from)
     }
 
-    fn shell(&self) -> Option<ArcStr> {
+    fn shell(&self) -> Option<Shell> {
         None
         // let pid = get_current_pid().ok()?;
         // let parent_pid = s.process(pid)?.parent()?;
@@ -451,7 +501,7 @@ impl OSInfo for WindowsInfo {
         // Some(parent.replace(".exe", ""))
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         let mut length = 0;
         let mut buf: Vec<u8>;
         let mut core_count = 0;
@@ -482,10 +532,14 @@ impl OSInfo for WindowsInfo {
             .ok()?
             .into();
         let freq: u32 = core0.get_value("~MHz").ok()?;
-        Some(arcstr::format!(
-            "{name} ({core_count}) @ {:.2}GHz",
-            freq as f32 / 1000.0
-        ))
+        Some(Cpu {
+            model: name,
+            sockets: None,
+            cores: Some(core_count),
+            threads: None,
+            freq_ghz: Some(f64::from(freq) / 1000.0),
+            temperature: None,
+        })
     }
 
     fn username(&self) -> std::option::Option<ArcStr> {
@@ -0,0 +1,199 @@
+//! Composes the logo and info column into plain rows of text, without touching the terminal
+
+use std::fmt::Display;
+
+use arcstr::ArcStr;
+use crossterm::style::{StyledContent, Stylize};
+
+use crate::config::LogoPosition;
+
+/// Columns between the logo and the info column
+const GAP: usize = 3;
+
+/// A row of (possibly styled) text, along with how many columns it takes up on screen
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Line {
+    pub text: String,
+    pub width: usize,
+}
+
+impl Line {
+    #[must_use]
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let width = visible_width(&text);
+        Self { text, width }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.width += visible_width(text);
+        self.text += text;
+    }
+
+    fn padded(&self, width: usize) -> String {
+        format!(
+            "{}{}",
+            self.text,
+            " ".repeat(width.saturating_sub(self.width))
+        )
+    }
+}
+
+/// Count the columns `text` takes up, skipping ANSI escape sequences
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // CSI sequences end with a byte in the range `@`..=`~`
+            if chars.next() == Some('[') {
+                chars.find(|ch| ('@'..='~').contains(ch));
+            }
+        } else if !ch.is_control() {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Split the colorized logo into lines, keeping each fragment's style
+pub fn logo_lines(icon: impl IntoIterator<Item = StyledContent<impl Display>>) -> Vec<Line> {
+    let mut lines = vec![Line::default()];
+    for chunk in icon {
+        let content = chunk.content().to_string();
+        for (idx, fragment) in content.split('\n').enumerate() {
+            if idx > 0 {
+                lines.push(Line::default());
+            }
+            if !fragment.is_empty() {
+                let styled = StyledContent::new(*chunk.style(), fragment).to_string();
+                let line = lines.last_mut().expect("lines is never empty");
+                line.width += fragment.chars().count();
+                line.text += &styled;
+            }
+        }
+    }
+    if lines.last().is_some_and(|line| line.width == 0) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Format `(label, value)` pairs as the info column
+#[must_use]
+pub fn info_lines(info: &[(ArcStr, ArcStr)]) -> Vec<Line> {
+    info.iter()
+        .map(|(property, value)| {
+            let mut line = Line::default();
+            line.push(&property.clone().bold().red().to_string());
+            if !property.is_empty() && !value.is_empty() {
+                line.push(&": ".bold().red().to_string());
+            }
+            line.push(&value.clone().reset().to_string());
+            line
+        })
+        .collect()
+}
+
+/// Place `right` beside `left`, padding `left` out to at least `left_width` columns
+#[must_use]
+pub fn side_by_side(left: &[Line], left_width: usize, right: &[Line]) -> Vec<String> {
+    let left_width = left
+        .iter()
+        .map(|line| line.width)
+        .max()
+        .unwrap_or_default()
+        .max(left_width);
+    let blank = Line::default();
+    (0..left.len().max(right.len()))
+        .map(|row| {
+            let left = left.get(row).unwrap_or(&blank);
+            right.get(row).map_or_else(
+                || left.text.clone(),
+                |right| left.padded(left_width + GAP) + &right.text,
+            )
+        })
+        .collect()
+}
+
+/// Arrange the logo and info column according to `position`
+#[must_use]
+pub fn compose(
+    logo: &[Line],
+    logo_width: usize,
+    info: &[Line],
+    position: LogoPosition,
+) -> Vec<String> {
+    let texts = |lines: &[Line]| {
+        lines
+            .iter()
+            .map(|line| line.text.clone())
+            .collect::<Vec<_>>()
+    };
+    if logo.is_empty() || info.is_empty() {
+        return texts(if logo.is_empty() { info } else { logo });
+    }
+    match position {
+        LogoPosition::Left => side_by_side(logo, logo_width, info),
+        LogoPosition::Right => side_by_side(info, 0, logo),
+        LogoPosition::Top => {
+            let mut rows = texts(logo);
+            rows.push(String::new());
+            rows.extend(texts(info));
+            rows
+        }
+        LogoPosition::None => texts(info),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[&str]) -> Vec<Line> {
+        texts.iter().map(|text| Line::new(*text)).collect()
+    }
+
+    #[test]
+    fn skips_escape_sequences() {
+        assert_eq!(visible_width("plain"), 5);
+        assert_eq!(visible_width("\x1b[38;5;9m\x1b[1mCPU\x1b[0m: 8"), 6);
+        assert_eq!(visible_width("héllo\t"), 5);
+        assert_eq!(visible_width(""), 0);
+    }
+
+    #[test]
+    fn pads_the_left_column() {
+        let left = lines(&["\x1b[31mab\x1b[0m", "abcd"]);
+        let right = lines(&["x", "y", "z"]);
+        assert_eq!(
+            side_by_side(&left, 0, &right),
+            ["\x1b[31mab\x1b[0m     x", "abcd   y", "       z"]
+        );
+        assert_eq!(
+            side_by_side(&left, 6, &right[..1]),
+            ["\x1b[31mab\x1b[0m       x", "abcd"]
+        );
+    }
+
+    #[test]
+    fn composes_by_position() {
+        let logo = lines(&["/\\", "\\/"]);
+        let info = lines(&["OS: Debian"]);
+        assert_eq!(
+            compose(&logo, 2, &info, LogoPosition::Left),
+            ["/\\   OS: Debian", "\\/"]
+        );
+        assert_eq!(
+            compose(&logo, 2, &info, LogoPosition::Right),
+            ["OS: Debian   /\\", "             \\/"]
+        );
+        assert_eq!(
+            compose(&logo, 2, &info, LogoPosition::Top),
+            ["/\\", "\\/", "", "OS: Debian"]
+        );
+        assert_eq!(compose(&logo, 2, &info, LogoPosition::None), ["OS: Debian"]);
+        assert_eq!(compose(&[], 0, &info, LogoPosition::Left), ["OS: Debian"]);
+        assert_eq!(compose(&logo, 2, &[], LogoPosition::Top), ["/\\", "\\/"]);
+    }
+}
@@ -7,4 +7,6 @@
 pub mod colorizer;
 pub mod config;
 pub mod info;
+pub mod layout;
+pub mod render;
 pub mod util;
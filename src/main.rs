@@ -6,39 +6,73 @@
 use anyhow::{anyhow, Ok, Result};
 use arcstr::ArcStr;
 use clap::Parser;
-use crossterm::{
-    cursor::{position, MoveTo, MoveToColumn, MoveToRow},
-    style::{Color, PrintStyledContent, Stylize},
-    ExecutableCommand,
-};
+use crossterm::style::Color;
 use directories::ProjectDirs;
 use mirafetch::{
     colorizer::{Colorizer, DefaultColors, FlagColors},
-    config::{Config, Orientation},
+    config::{Config, LogoPosition, Orientation},
+    info, layout, render,
     util::{get_colorscheme, get_icon, AsciiArt},
 };
-use std::{fmt::Display, fs, io::stdout, process::ExitCode, sync::Arc};
+use std::thread;
 use std::{
-    sync::mpsc,
-    thread::{self},
+    fmt::Display,
+    fs,
+    io::{stdout, Write},
+    process::ExitCode,
+    sync::Arc,
 };
-mod info;
-mod util;
 
 fn main() -> anyhow::Result<std::process::ExitCode> {
     let settings = load_settings_file()?.with_config(Config::parse());
+    if let Some(format) = settings.format {
+        println!(
+            "{}",
+            render::serialize(
+                &info::gather(
+                    &settings.format_modules(),
+                    &settings.disks,
+                    &settings.network
+                ),
+                format
+            )?
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
     let scheme = get_colorscheme_from_settings(&settings);
+    let position = settings.logo_position();
 
-    let (tx, rx) = mpsc::channel();
-    let id = info::get_id();
-    let logo: AsciiArt = get_icon(&get_os_id(&settings, &id))?;
-    let colored_logo = colorize_logo(settings.orientation, &scheme, &logo)?;
-    thread::spawn(move || {
-        info::get_async(&tx);
+    let modules = if settings.logo_only {
+        Vec::new()
+    } else {
+        settings.modules()
+    };
+    let formats = settings.formats.clone();
+    let disks = settings.disks.clone();
+    let network = settings.network.clone();
+    let options = render::Options::from(&settings);
+    let gatherer = thread::spawn(move || {
+        render::lines(
+            &info::gather(&modules, &disks, &network),
+            &modules,
+            &formats,
+            options,
+        )
     });
+    let (logo_lines, logo_width) = if position == LogoPosition::None {
+        (Vec::new(), 0)
+    } else {
+        let id = info::get_id();
+        let logo: AsciiArt = get_icon(&get_os_id(&settings, &id))?;
+        let colored_logo = colorize_logo(settings.orientation, scheme.as_ref(), &logo)?;
+        (layout::logo_lines(colored_logo), usize::from(logo.width))
+    };
+    let info = gatherer
+        .join()
+        .map_err(|_| anyhow!("Failed to gather system info"))?;
 
     // Show system info
-    display(colored_logo, rx, &logo).ok();
+    display(&logo_lines, logo_width, &info, position)?;
 
     Ok(ExitCode::SUCCESS)
 }
@@ -46,9 +80,8 @@ fn main() -> anyhow::Result<std::process::ExitCode> {
 fn get_os_id(settings: &Config, default: &impl ToString) -> impl ToString {
     settings
         .icon_name
-        .as_ref()
-        .unwrap_or(&default.to_string())
-        .to_owned()
+        .clone()
+        .unwrap_or_else(|| default.to_string())
 }
 
 fn get_colorscheme_from_settings(settings: &Config) -> Option<Arc<[Color]>> {
@@ -71,7 +104,7 @@ fn load_settings_file() -> Result<Config, anyhow::Error> {
             let config_path = dir.config_dir().join("config.toml");
             if !config_path.exists() {
                 return anyhow::Ok(Config::default());
-            };
+            }
             let config_file = fs::read_to_string(config_path)?;
             toml::from_str::<Config>(&config_file).map_err(|err| {
                 eprintln!("Invalid config: {err}");
@@ -83,7 +116,7 @@ fn load_settings_file() -> Result<Config, anyhow::Error> {
 
 fn colorize_logo(
     orientation: Option<Orientation>,
-    scheme: &Option<Arc<[Color]>>,
+    scheme: Option<&Arc<[Color]>>,
     logo: &AsciiArt,
 ) -> Result<impl IntoIterator<Item = crossterm::style::StyledContent<impl Display>>, anyhow::Error>
 {
@@ -102,33 +135,19 @@ fn colorize_logo(
 ///
 /// # Errors
 ///
-/// This function will return an error if the terminal settings (eg color, cursor position) cannot be modified
+/// This function will return an error if stdout cannot be written to
 fn display(
-    icon: impl IntoIterator<Item = crossterm::style::StyledContent<impl Display>>,
-    info: impl IntoIterator<Item = (ArcStr, ArcStr)>,
-    logo: &AsciiArt,
+    logo: &[layout::Line],
+    logo_width: usize,
+    info: &[(ArcStr, ArcStr)],
+    position: LogoPosition,
 ) -> Result<(), anyhow::Error> {
-    let mut out = stdout();
-    // print icon
-    println!();
-    //let (_, top) = position()?;
-    for line in icon {
-        out.execute(PrintStyledContent(line))?;
-    }
-    let (_, logo_bottom) = position()?;
-    out.execute(MoveTo(0, logo_bottom - logo.height + 1))?;
-    // print system info
-    for (property, value) in info {
-        out.execute(MoveToColumn(logo.width + 3))?
-            .execute(PrintStyledContent(property.clone().bold().red()))?;
-        if !property.is_empty() && !value.is_empty() {
-            out.execute(PrintStyledContent(": ".bold().red()))?;
-        }
-        out.execute(PrintStyledContent(value.reset()))?;
-        println!();
+    let rows = layout::compose(logo, logo_width, &layout::info_lines(info), position);
+    let mut out = stdout().lock();
+    writeln!(out)?;
+    for row in rows {
+        writeln!(out, "{row}")?;
     }
-    let (_, info_bottom) = position()?;
-    out.execute(MoveToRow(std::cmp::max(info_bottom, logo_bottom)))?;
-    println!();
+    writeln!(out)?;
     Ok(())
 }
@@ -0,0 +1,1049 @@
+//! Turns gathered [`Info`] into labelled, human-readable lines
+
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+use arcstr::ArcStr;
+use crossterm::style::{Color, Stylize};
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    config::{Config, ModuleFormat, OutputFormat, TemperatureUnit, UptimeStyle},
+    info::{
+        Battery, BootTime, Cpu, Cursor, Disk, Drive, DriveKind, Font, Gateway, Gpu, GpuKind, Info,
+        Interface, InterfaceKind, IpNet, Memory, Module, Monitor, PackageCount, Protocol, Routing,
+        Shell, Swap, Terminal, Wifi, Wm,
+    },
+    util::bytecount_format,
+};
+
+impl Display for Cpu {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(sockets) = self.sockets.filter(|sockets| *sockets > 1) {
+            write!(f, "{sockets}x ")?;
+        }
+        write!(f, "{}", self.model)?;
+        if let Some(count) = self.threads.or(self.cores) {
+            write!(f, " ({count})")?;
+        }
+        if let Some(freq) = self.freq_ghz {
+            write!(f, " @ {freq:.2} GHz")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Gpu {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.vendor {
+            Some(vendor) => write!(f, "{vendor} {}", self.model)?,
+            None => write!(f, "{}", self.model)?,
+        }
+        let details = [
+            self.kind.map(|kind| kind.to_string()),
+            self.driver.as_ref().map(ToString::to_string),
+            self.vram.map(|vram| bytecount_format(vram.total, 1)),
+        ];
+        let details = details.iter().flatten().join(", ");
+        if !details.is_empty() {
+            write!(f, " ({details})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for GpuKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integrated => write!(f, "Integrated"),
+            Self::Discrete => write!(f, "Discrete"),
+        }
+    }
+}
+
+impl Display for Memory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 2),
+            bytecount_format(self.total, 2)
+        )
+    }
+}
+
+impl Display for Disk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 0),
+            bytecount_format(self.total, 0),
+        )?;
+        if self.total > 0 {
+            write!(f, " ({}%)", percent(self.used, self.total))?;
+        }
+        if let Some(fs) = &self.fs {
+            write!(f, " - {fs}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Battery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let percent = self
+            .percent
+            .map_or_else(|| String::from("\u{221e}"), |percent| percent.to_string());
+        write!(f, "{percent}%")?;
+        if let Some(status) = &self.status {
+            write!(f, " {status}")?;
+        }
+        let charging = self.status.as_deref() == Some("Charging");
+        let notes = [
+            self.power.map(|power| format!("{power:.1} W")),
+            self.time_remaining.map(|time| {
+                let until = if charging { "until full" } else { "left" };
+                format!("{} {until}", hours_minutes(time))
+            }),
+            self.health.map(|health| format!("{health:.0}% health")),
+            self.ac_online
+                .map(|online| String::from(if online { "AC connected" } else { "on battery" })),
+        ];
+        let notes = notes.iter().flatten().join(", ");
+        if !notes.is_empty() {
+            write!(f, " ({notes})")?;
+        }
+        Ok(())
+    }
+}
+
+fn battery_label(battery: &Battery) -> String {
+    // Peripherals are easier to recognise by their model than by their driver's name
+    let name = match &battery.model {
+        Some(model) if battery.peripheral => model,
+        _ => &battery.name,
+    };
+    format!("Battery ({name})")
+}
+
+/// e.g. `3h 20m`, or `45m` under an hour
+fn hours_minutes(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+impl Display for Monitor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "{name} ")?;
+        }
+        write!(f, "{}x{}", self.width, self.height)?;
+        if let Some(refresh) = self.refresh_hz {
+            write!(f, " @ {refresh:.0}Hz")?;
+        }
+        if let Some(diagonal) = self.diagonal {
+            write!(f, " {}″", inches(diagonal))?;
+        }
+        if let Some(connector) = &self.connector {
+            write!(f, " [{connector}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Round to a tenth of an inch, dropping the decimal for whole sizes, e.g. `27` or `13.3`
+fn inches(diagonal: f64) -> String {
+    let inches = format!("{diagonal:.1}");
+    inches
+        .strip_suffix(".0")
+        .map_or_else(|| inches.clone(), str::to_owned)
+}
+
+impl Display for Swap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 2),
+            bytecount_format(self.total, 2)
+        )?;
+        if let Some(ratio) = compression_ratio(self) {
+            write!(f, ", {ratio:.1}x compression")?;
+        }
+        Ok(())
+    }
+}
+
+fn compression_ratio(swap: &Swap) -> Option<f64> {
+    let (original, compressed) = (swap.original?, swap.compressed?);
+    (compressed > 0).then(|| original as f64 / compressed as f64)
+}
+
+/// The parts of memory that are in use but not by processes, e.g. `buffers 120 MiB, cached 4 GiB`
+fn memory_details(memory: &Memory) -> Option<String> {
+    let details = [
+        ("buffers", memory.buffers),
+        ("cached", memory.cached),
+        ("shared", memory.shared),
+        ("huge pages", memory.huge_pages.filter(|size| *size > 0)),
+    ]
+    .iter()
+    .filter_map(|(name, size)| Some(format!("{name} {}", bytecount_format((*size)?, 2))))
+    .join(", ");
+    (!details.is_empty()).then_some(details)
+}
+
+impl Display for Interface {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", join(&self.addrs))?;
+        let duplex = self
+            .duplex
+            .as_ref()
+            .map(|duplex| format!(" {duplex} duplex"));
+        let speed = self
+            .speed
+            .map(|speed| link_speed(speed) + duplex.as_deref().unwrap_or_default());
+        let notes = [self.kind.map(|kind| kind.to_string()), speed];
+        let notes = notes.iter().flatten().join(", ");
+        if !notes.is_empty() {
+            write!(f, " ({notes})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Wifi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let notes = [
+            self.signal.map(|signal| format!("{signal} dBm")),
+            self.frequency.and_then(band).map(String::from),
+        ];
+        let notes = notes.iter().flatten().join(", ");
+        match self.quality {
+            Some(quality) if notes.is_empty() => write!(f, "{quality}%"),
+            Some(quality) => write!(f, "{quality}% ({notes})"),
+            None => write!(f, "{notes}"),
+        }
+    }
+}
+
+/// The band a Wi-Fi frequency in MHz is in
+const fn band(frequency: u32) -> Option<&'static str> {
+    match frequency {
+        2400..=2500 => Some("2.4 GHz"),
+        4900..=5924 => Some("5 GHz"),
+        5925..=7125 => Some("6 GHz"),
+        57000..=71000 => Some("60 GHz"),
+        _ => None,
+    }
+}
+
+impl Display for Gateway {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.addr, self.interface)
+    }
+}
+
+/// A line per default gateway, then the DNS servers and VPNs if there are any
+fn routing_entries(routing: &Routing) -> Vec<Entry> {
+    let gateways = routing
+        .gateways
+        .iter()
+        .map(|gateway| Entry::new("Gateway", gateway));
+    let dns = (!routing.dns.is_empty()).then(|| Entry::new("DNS", &join(&routing.dns)));
+    let vpns = (!routing.vpns.is_empty()).then(|| Entry::new("VPN", &join(&routing.vpns)));
+    gateways.chain(dns).chain(vpns).collect()
+}
+
+/// e.g. `100 Mbps` or `2.5 Gbps`
+fn link_speed(mbps: u32) -> String {
+    if mbps >= 1000 {
+        format!("{} Gbps", f64::from(mbps) / 1000.0)
+    } else {
+        format!("{mbps} Mbps")
+    }
+}
+
+impl Display for InterfaceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ethernet => write!(f, "Ethernet"),
+            Self::Wifi => write!(f, "Wi-Fi"),
+            Self::Vpn => write!(f, "VPN"),
+            Self::Bridge => write!(f, "Bridge"),
+            Self::Virtual => write!(f, "Virtual"),
+        }
+    }
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl Display for Drive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let model = self.model.as_ref().unwrap_or(&self.name);
+        let kind = self.kind.map(|kind| kind.to_string());
+        // `NVMe` already says how an NVMe drive is connected
+        let transport = self
+            .transport
+            .as_ref()
+            .filter(|transport| self.kind != Some(DriveKind::Nvme) || *transport != "nvme")
+            .map(|transport| transport.to_uppercase());
+        let details = [
+            Some(bytecount_format(self.size, 1)),
+            kind,
+            transport,
+            self.removable.then(|| String::from("removable")),
+        ];
+        write!(f, "{model} ({})", details.iter().flatten().join(", "))
+    }
+}
+
+impl Display for DriveKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hdd => write!(f, "HDD"),
+            Self::Ssd => write!(f, "SSD"),
+            Self::Nvme => write!(f, "NVMe"),
+        }
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X11 => write!(f, "X11"),
+            Self::Wayland => write!(f, "Wayland"),
+        }
+    }
+}
+
+impl Display for Wm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(protocol) = self.protocol {
+            write!(f, " ({protocol})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.theme)?;
+        if let Some(size) = self.size {
+            write!(f, " ({size}px)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Font {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(size) = self.size {
+            write!(f, " ({size}pt)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {version}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Terminal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ssh = self
+            .ssh_client
+            .as_ref()
+            .map(|client| format!("SSH from {client}"));
+        let mut notes = self.multiplexer.iter().map(ArcStr::to_string).collect_vec();
+        match (&self.name, ssh) {
+            (Some(name), ssh) => {
+                write!(f, "{name}")?;
+                notes.extend(ssh);
+            }
+            (None, Some(ssh)) => write!(f, "{ssh}")?,
+            (None, None) => {}
+        }
+        if !notes.is_empty() {
+            write!(f, " ({})", notes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Wraps an uptime so it can be formatted like the other modules
+struct Uptime(Duration, UptimeStyle);
+
+impl Display for Uptime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.1.format(self.0))
+    }
+}
+
+impl BootTime {
+    /// The date and time in the timezone it was recorded in
+    fn local(&self) -> Option<time::OffsetDateTime> {
+        let offset = time::UtcOffset::from_whole_seconds(self.utc_offset).ok()?;
+        let utc = time::OffsetDateTime::from_unix_timestamp(self.timestamp).ok()?;
+        Some(utc.to_offset(offset))
+    }
+
+    /// e.g. `2024-03-09`
+    fn date(&self) -> Option<String> {
+        let local = self.local()?;
+        let (year, month, day) = (local.year(), u8::from(local.month()), local.day());
+        Some(format!("{year}-{month:02}-{day:02}"))
+    }
+
+    /// e.g. `08:05`
+    fn time(&self) -> Option<String> {
+        let local = self.local()?;
+        Some(format!("{:02}:{:02}", local.hour(), local.minute()))
+    }
+}
+
+impl Display for BootTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (Some(date), Some(time)) = (self.date(), self.time()) else {
+            return write!(f, "{}", self.timestamp);
+        };
+        write!(f, "{date} {time}")?;
+        if let Some(timezone) = &self.timezone {
+            write!(f, " {timezone}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Wraps the package counts so they can be formatted as a single line
+struct Packages<'a>(&'a [PackageCount]);
+
+impl Display for Packages<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let counts = self
+            .0
+            .iter()
+            .map(|package| format!("{} ({})", package.count, package.manager));
+        write!(f, "{}", counts.format(", "))
+    }
+}
+
+/// Named values that a module's `format` or `label` template can refer to, e.g. `{used}`
+pub trait Fields: Display {
+    fn fields(&self) -> Vec<(&'static str, String)>;
+}
+
+fn optional(value: Option<impl Display>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn optional_bytes(value: Option<u64>) -> String {
+    optional(value.map(|value| bytecount_format(value, 2)))
+}
+
+fn percent(used: u64, total: u64) -> String {
+    if total == 0 {
+        return String::new();
+    }
+    format!("{:.0}", used as f64 / total as f64 * 100.0)
+}
+
+impl Fields for ArcStr {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+impl Fields for Cpu {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("model", self.model.to_string()),
+            ("sockets", optional(self.sockets)),
+            ("cores", optional(self.cores)),
+            ("threads", optional(self.threads)),
+            (
+                "freq_ghz",
+                optional(self.freq_ghz.map(|freq| format!("{freq:.2}"))),
+            ),
+        ]
+    }
+}
+
+impl Fields for Gpu {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("vendor", optional(self.vendor.as_ref())),
+            ("model", self.model.to_string()),
+            ("driver", optional(self.driver.as_ref())),
+            ("kind", optional(self.kind)),
+            (
+                "vram_used",
+                optional(self.vram.map(|vram| bytecount_format(vram.used, 2))),
+            ),
+            (
+                "vram_total",
+                optional(self.vram.map(|vram| bytecount_format(vram.total, 1))),
+            ),
+        ]
+    }
+}
+
+impl Fields for Memory {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("used", bytecount_format(self.used, 2)),
+            ("total", bytecount_format(self.total, 2)),
+            ("percent", percent(self.used, self.total)),
+            ("buffers", optional_bytes(self.buffers)),
+            ("cached", optional_bytes(self.cached)),
+            ("shared", optional_bytes(self.shared)),
+            ("huge_pages", optional_bytes(self.huge_pages)),
+        ]
+    }
+}
+
+impl Fields for Swap {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("device", optional(self.zram.as_ref())),
+            ("used", bytecount_format(self.used, 2)),
+            ("total", bytecount_format(self.total, 2)),
+            ("percent", percent(self.used, self.total)),
+            (
+                "ratio",
+                optional(compression_ratio(self).map(|ratio| format!("{ratio:.1}"))),
+            ),
+        ]
+    }
+}
+
+impl Fields for Disk {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("mount", self.mount.to_string()),
+            ("fs", optional(self.fs.as_ref())),
+            ("used", bytecount_format(self.used, 0)),
+            ("total", bytecount_format(self.total, 0)),
+            ("percent", percent(self.used, self.total)),
+        ]
+    }
+}
+
+impl Fields for Interface {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let addrs = |ipv6: bool| {
+            let addrs = self.addrs.iter().filter(|addr| addr.addr.is_ipv6() == ipv6);
+            addrs.copied().collect_vec()
+        };
+        vec![
+            ("name", self.name.to_string()),
+            ("addrs", join(&self.addrs).to_string()),
+            ("ipv4", join(&addrs(false)).to_string()),
+            ("ipv6", join(&addrs(true)).to_string()),
+            ("kind", optional(self.kind)),
+            ("mac", optional(self.mac.as_ref())),
+            ("speed", optional(self.speed.map(link_speed))),
+            ("duplex", optional(self.duplex.as_ref())),
+        ]
+    }
+}
+
+impl Fields for Wifi {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("interface", self.interface.to_string()),
+            ("quality", optional(self.quality)),
+            ("signal", optional(self.signal)),
+            ("frequency", optional(self.frequency)),
+            ("band", optional(self.frequency.and_then(band))),
+        ]
+    }
+}
+
+impl Fields for Gateway {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("addr", self.addr.to_string()),
+            ("interface", self.interface.to_string()),
+        ]
+    }
+}
+
+impl Fields for Drive {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.to_string()),
+            ("model", optional(self.model.as_ref())),
+            ("size", bytecount_format(self.size, 1)),
+            ("kind", optional(self.kind)),
+            ("transport", optional(self.transport.as_ref())),
+            ("removable", self.removable.to_string()),
+        ]
+    }
+}
+
+impl Fields for Battery {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.to_string()),
+            ("model", optional(self.model.as_ref())),
+            ("percent", optional(self.percent)),
+            ("status", optional(self.status.as_ref())),
+            (
+                "health",
+                optional(self.health.map(|health| format!("{health:.0}"))),
+            ),
+            (
+                "power",
+                optional(self.power.map(|power| format!("{power:.1}"))),
+            ),
+            (
+                "time_remaining",
+                optional(self.time_remaining.map(hours_minutes)),
+            ),
+            ("ac_online", optional(self.ac_online)),
+            ("peripheral", self.peripheral.to_string()),
+        ]
+    }
+}
+
+impl Fields for Wm {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.to_string()),
+            ("protocol", optional(self.protocol)),
+        ]
+    }
+}
+
+impl Fields for Cursor {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("theme", self.theme.to_string()),
+            ("size", optional(self.size)),
+        ]
+    }
+}
+
+impl Fields for Font {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.to_string()),
+            ("size", optional(self.size)),
+        ]
+    }
+}
+
+impl Fields for Shell {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.to_string()),
+            ("version", optional(self.version.as_ref())),
+        ]
+    }
+}
+
+impl Fields for Terminal {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", optional(self.name.as_ref())),
+            ("multiplexer", optional(self.multiplexer.as_ref())),
+            ("ssh_client", optional(self.ssh_client.as_ref())),
+        ]
+    }
+}
+
+impl Fields for Packages<'_> {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let total = self.0.iter().map(|package| package.count).sum::<u64>();
+        self.0
+            .iter()
+            .map(|package| (package.manager, package.count.to_string()))
+            .chain([("total", total.to_string())])
+            .collect()
+    }
+}
+
+impl Fields for Monitor {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", optional(self.name.as_ref())),
+            ("connector", optional(self.connector.as_ref())),
+            ("width", self.width.to_string()),
+            ("height", self.height.to_string()),
+            (
+                "refresh",
+                optional(self.refresh_hz.map(|refresh| format!("{refresh:.0}"))),
+            ),
+            ("max_refresh", optional(self.max_refresh_hz)),
+            ("diagonal", optional(self.diagonal.map(inches))),
+        ]
+    }
+}
+
+impl Fields for BootTime {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("date", optional(self.date())),
+            ("time", optional(self.time())),
+            ("timezone", optional(self.timezone.as_ref())),
+            ("timestamp", self.timestamp.to_string()),
+        ]
+    }
+}
+
+impl Fields for Uptime {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let seconds = self.0.as_secs();
+        vec![
+            ("days", (seconds / 86400).to_string()),
+            ("hours", (seconds / 3600 % 24).to_string()),
+            ("minutes", (seconds / 60 % 60).to_string()),
+            ("seconds", (seconds % 60).to_string()),
+        ]
+    }
+}
+
+/// Replace each `{name}` in `template` with the matching field, leaving unknown names as-is
+fn fill_template(template: &str, fields: &[(&'static str, String)]) -> String {
+    let mut res = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        res += &rest[..start];
+        let placeholder = &rest[start..=start + len];
+        let name = &placeholder[1..placeholder.len() - 1];
+        match fields.iter().find(|(field, _)| *field == name) {
+            Some((_, value)) => res += value,
+            None => res += placeholder,
+        }
+        rest = &rest[start + len + 1..];
+    }
+    res + rest
+}
+
+/// A line of the info column, before the user's label and format are applied
+struct Entry {
+    label: String,
+    value: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Entry {
+    fn new(label: impl Into<String>, value: &impl Fields) -> Self {
+        let mut fields = value.fields();
+        let value = value.to_string();
+        fields.push(("value", value.clone()));
+        Self {
+            label: label.into(),
+            value,
+            fields,
+        }
+    }
+
+    fn numbered(label: &str, values: &[impl Fields]) -> Vec<Self> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                let index = (idx + 1).to_string();
+                let mut entry = Self::new(format!("{label} {index}"), value);
+                entry.fields.push(("index", index));
+                entry
+            })
+            .collect()
+    }
+
+    /// A line per value, labelled by `label`
+    fn each<T: Fields>(values: &[T], label: impl Fn(&T) -> String) -> Vec<Self> {
+        values
+            .iter()
+            .map(|value| Self::new(label(value), value))
+            .collect()
+    }
+
+    fn single(label: &str, value: Option<&impl Fields>) -> Vec<Self> {
+        value
+            .map(|value| Self::new(label, value))
+            .into_iter()
+            .collect()
+    }
+
+    /// Add to the end of the default formatting, keeping the `{value}` field in sync
+    fn append(&mut self, text: &str) {
+        self.value += text;
+        for (name, value) in &mut self.fields {
+            if *name == "value" {
+                value.clone_from(&self.value);
+            }
+        }
+    }
+
+    /// Append a temperature to the value, e.g. `... — 54°C`, and add a `{temperature}` field
+    fn with_temperature(mut self, celsius: Option<f64>, unit: TemperatureUnit) -> Self {
+        let temperature = celsius.map(|celsius| unit.format(celsius));
+        if let Some(temperature) = &temperature {
+            self.append(&format!(" — {temperature}"));
+        }
+        self.fields.push(("temperature", optional(temperature)));
+        self
+    }
+
+    fn render(self, format: Option<&ModuleFormat>) -> (ArcStr, ArcStr) {
+        let label = format
+            .and_then(|format| format.label.as_deref())
+            .map_or(self.label, |label| fill_template(label, &self.fields));
+        let value = format
+            .and_then(|format| format.format.as_deref())
+            .map_or(self.value, |value| fill_template(value, &self.fields));
+        (ArcStr::from(label), ArcStr::from(value))
+    }
+}
+
+fn join(items: &[impl Display]) -> ArcStr {
+    ArcStr::from(items.iter().join(", "))
+}
+
+/// Settings that change how values are displayed, rather than which are shown
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    pub temperature_unit: TemperatureUnit,
+    pub uptime_style: UptimeStyle,
+    /// Show buffers, cache, shared memory and huge pages after memory usage
+    pub detailed_memory: bool,
+}
+
+impl From<&Config> for Options {
+    fn from(config: &Config) -> Self {
+        Self {
+            temperature_unit: config.temperature_unit.unwrap_or_default(),
+            uptime_style: config.uptime_style.unwrap_or_default(),
+            detailed_memory: config.detailed_memory,
+        }
+    }
+}
+
+/// Serialize the raw gathered info, for use by scripts
+///
+/// # Errors
+///
+/// This function will return an error if `info` cannot be represented in the requested format
+pub fn serialize(info: &Info, format: OutputFormat) -> anyhow::Result<String> {
+    Ok(match format {
+        OutputFormat::Json => serde_json::to_string_pretty(info)?,
+        OutputFormat::Yaml => serde_yaml::to_string(info)?,
+        OutputFormat::Toml => toml::to_string(info)?,
+    })
+}
+
+/// Render the requested modules as `(label, value)` pairs, in order, applying any custom labels
+/// and formats
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn lines(
+    info: &Info,
+    modules: &[Module],
+    formats: &FxHashMap<Module, ModuleFormat>,
+    options: Options,
+) -> Vec<(ArcStr, ArcStr)> {
+    modules
+        .iter()
+        .flat_map(|module| {
+            let format = formats.get(module);
+            let lines = module_entries(info, *module, options)
+                .into_iter()
+                .map(|entry| entry.render(format));
+            if *module == Module::Title {
+                // Underline the title, however it ended up being formatted
+                return lines
+                    .flat_map(|(title, value)| {
+                        let underline = ArcStr::from("-".repeat(title.chars().count()));
+                        [(title, value), (underline, ArcStr::new())]
+                    })
+                    .collect_vec();
+            }
+            lines.collect_vec()
+        })
+        .collect()
+}
+
+fn module_entries(info: &Info, module: Module, options: Options) -> Vec<Entry> {
+    match module {
+        Module::Title => {
+            let username = info.username.clone().unwrap_or_default();
+            let hostname = info.hostname.clone().unwrap_or_default();
+            vec![Entry {
+                label: format!("{username}@{hostname}"),
+                value: String::new(),
+                fields: vec![
+                    ("username", username.to_string()),
+                    ("hostname", hostname.to_string()),
+                ],
+            }]
+        }
+        Module::Os => Entry::single("OS", info.os.as_ref()),
+        Module::Machine => Entry::single("Machine", info.machine.as_ref()),
+        Module::Kernel => Entry::single("Kernel", info.kernel.as_ref()),
+        Module::Uptime => Entry::single(
+            "Uptime",
+            info.uptime
+                .map(|uptime| Uptime(uptime, options.uptime_style))
+                .as_ref(),
+        ),
+        Module::BootTime => Entry::single("Boot Time", info.boot_time.as_ref()),
+        Module::Packages => Entry::single(
+            "Packages",
+            Some(Packages(&info.packages))
+                .filter(|packages| !packages.0.is_empty())
+                .as_ref(),
+        ),
+        Module::Shell => Entry::single("Shell", info.shell.as_ref()),
+        Module::Displays => Entry::numbered("Display", &info.displays),
+        Module::De => Entry::single("DE", info.de.as_ref()),
+        Module::Wm => Entry::single("WM", info.wm.as_ref()),
+        Module::Theme => Entry::single("Theme", info.theme.as_ref()),
+        Module::Icons => Entry::single("Icons", info.icons.as_ref()),
+        Module::Font => Entry::single("Font", info.font.as_ref()),
+        Module::Cursor => Entry::single("Cursor", info.cursor.as_ref()),
+        Module::Terminal => Entry::single("Terminal", info.terminal.as_ref()),
+        Module::TerminalFont => Entry::single("Terminal Font", info.terminal_font.as_ref()),
+        Module::Cpu => Entry::single("CPU", info.cpu.as_ref())
+            .into_iter()
+            .map(|entry| {
+                let celsius = info.cpu.as_ref().and_then(|cpu| cpu.temperature);
+                entry.with_temperature(celsius, options.temperature_unit)
+            })
+            .collect(),
+        Module::Gpu => Entry::numbered("GPU", &info.gpus)
+            .into_iter()
+            .zip(&info.gpus)
+            .map(|(entry, gpu)| entry.with_temperature(gpu.temperature, options.temperature_unit))
+            .collect(),
+        Module::Memory => Entry::single("Memory", info.memory.as_ref())
+            .into_iter()
+            .map(|mut entry| {
+                if options.detailed_memory {
+                    if let Some(details) = info.memory.as_ref().and_then(memory_details) {
+                        entry.append(&format!(" ({details})"));
+                    }
+                }
+                entry
+            })
+            .collect(),
+        Module::Swap => Entry::each(&info.swap, |swap| {
+            swap.zram
+                .as_ref()
+                .map_or_else(|| String::from("Swap"), |zram| format!("Swap ({zram})"))
+        }),
+        Module::Disks => Entry::each(&info.disks, |disk| format!("Disk ({})", disk.mount)),
+        Module::Storage => Entry::each(&info.storage, |drive| format!("Storage ({})", drive.name)),
+        Module::Battery => Entry::each(&info.battery, battery_label),
+        Module::Ip => Entry::each(&info.ip, |interface| format!("IP ({})", interface.name)),
+        Module::Wifi => Entry::each(&info.wifi, |wifi| format!("Wi-Fi ({})", wifi.interface)),
+        Module::Routing => info.routing.as_ref().map_or_else(Vec::new, routing_entries),
+        Module::Locale => Entry::single("Locale", info.locale.as_ref()),
+        Module::Hostname => Entry::single("Hostname", info.hostname.as_ref()),
+        Module::Username => Entry::single("Username", info.username.as_ref()),
+        Module::Palette => {
+            let (dark, light) = palette();
+            vec![Entry::new("", &dark), Entry::new("", &light)]
+        }
+    }
+}
+
+fn palette() -> (ArcStr, ArcStr) {
+    (
+        (0..8u8)
+            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
+            .collect::<String>()
+            .into(),
+        (8..16u8)
+            .map(|x| "   ".on(Color::AnsiValue(x)).to_string())
+            .collect::<String>()
+            .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu() -> Cpu {
+        Cpu {
+            model: ArcStr::from("AMD Ryzen 7 5800X"),
+            sockets: Some(1),
+            cores: Some(8),
+            threads: Some(16),
+            freq_ghz: None,
+            temperature: None,
+        }
+    }
+
+    #[test]
+    fn fills_known_fields() {
+        let entry = Entry::new("CPU", &cpu());
+        assert_eq!(
+            fill_template("{model} ({cores}C/{threads}T)", &entry.fields),
+            "AMD Ryzen 7 5800X (8C/16T)"
+        );
+        assert_eq!(fill_template("{value}", &entry.fields), entry.value);
+    }
+
+    #[test]
+    fn leaves_unknown_fields() {
+        let fields = cpu().fields();
+        assert_eq!(
+            fill_template("{model} {vendor}", &fields),
+            "AMD Ryzen 7 5800X {vendor}"
+        );
+        assert_eq!(fill_template("{}", &fields), "{}");
+        assert_eq!(fill_template("{model", &fields), "{model");
+        assert_eq!(fill_template("{cores} {", &fields), "8 {");
+    }
+
+    #[test]
+    fn leaves_out_percent_of_empty_disks() {
+        let disk = |used, total| Disk {
+            mount: ArcStr::from("/"),
+            fs: Some(ArcStr::from("btrfs")),
+            used,
+            total,
+        };
+        assert_eq!(
+            disk(256 << 30, 1 << 40).to_string(),
+            "256 GiB / 1 TiB (25%) - btrfs"
+        );
+        assert_eq!(disk(0, 0).to_string(), "0 bytes / 0 bytes - btrfs");
+        assert_eq!(fill_template("{percent}", &disk(0, 0).fields()), "");
+    }
+
+    #[test]
+    fn empties_missing_fields() {
+        let fields = cpu().fields();
+        assert_eq!(
+            fill_template("{model} @ {freq_ghz}", &fields),
+            "AMD Ryzen 7 5800X @ "
+        );
+    }
+}
@@ -1,12 +1,16 @@
 use anyhow::anyhow;
 use crossterm::style::Color;
-use lazy_static::lazy_static;
 use num::Unsigned;
 use regex::Regex;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeAs};
-use std::{iter::zip, num::ParseIntError, str::FromStr, sync::Arc};
+use std::{
+    iter::zip,
+    num::ParseIntError,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
 
 // TODO: see if you can have these as structs set at compile time
 const ICONS: &str = include_str!("../data/icons.yaml");
@@ -25,8 +29,7 @@ pub fn get_icon(icon_name: &impl ToString) -> anyhow::Result<AsciiArt> {
         .map(|x| TryInto::<AsciiArt>::try_into(x).expect("Could not parse icon"));
     icons
         .into_iter()
-        .find(|item| item.name.contains(&icon_name.to_string()))
-        .map(std::convert::Into::into)
+        .find(|item| item.name.contains(icon_name))
         .ok_or_else(|| anyhow!(format!("Could not find an icon for {icon_name}")))
 }
 
@@ -69,13 +72,14 @@ struct AsciiArtUnprocessed {
     pub width: u16,
     pub art: String,
 }
-lazy_static! {
-    static ref ascii_regex: Regex = Regex::new(r"\$\{c(\d*)\}").unwrap();
+fn ascii_regex() -> &'static Regex {
+    static ASCII_REGEX: OnceLock<Regex> = OnceLock::new();
+    ASCII_REGEX.get_or_init(|| Regex::new(r"\$\{c(\d*)\}").unwrap())
 }
 impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
     fn try_from(val: AsciiArtUnprocessed) -> anyhow::Result<Self> {
         let height = u16::try_from(val.art.lines().count())?;
-        let color_idx: Vec<u8> = ascii_regex
+        let color_idx: Vec<u8> = ascii_regex()
             .captures_iter(&val.art)
             .map(|x| -> anyhow::Result<u8> {
                 str::parse(
@@ -87,7 +91,7 @@ impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
             })
             .map(std::result::Result::unwrap)
             .collect();
-        let chunks = ascii_regex
+        let chunks = ascii_regex()
             .split(&val.art)
             .map(std::borrow::ToOwned::to_owned)
             .skip(1)